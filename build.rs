@@ -0,0 +1,31 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Embed the bundled logos, so they are found wherever the binary runs
+fn main() {
+    let dir = Path::new("src/logo/ascii");
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let mut logos: Vec<_> = fs::read_dir(dir)
+        .expect("src/logo/ascii is missing")
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    logos.sort();
+
+    let mut table = String::from("&[\n");
+    for path in logos {
+        let name = path.file_stem().unwrap().to_string_lossy();
+        let path = fs::canonicalize(&path).unwrap();
+        table.push_str(&format!(
+            "    ({name:?}, include_str!({:?})),\n",
+            path.display().to_string()
+        ));
+    }
+    table.push_str("]\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("bundled_logos.rs");
+    fs::write(out, table).expect("failed to write the bundled logo table");
+}
@@ -0,0 +1,46 @@
+use crate::dmi;
+use crate::sysroot::SysRoot;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Battery {
+    /// Kernel name, e.g. `BAT0`
+    pub name: String,
+    pub model: Option<String>,
+    /// Charge level in percent
+    pub capacity: u32,
+    /// `Charging`, `Discharging`, `Full`, ...
+    pub status: Option<String>,
+}
+
+/// System batteries; peripherals reporting their charge (mice, gamepads)
+/// are left out
+pub fn detect_batteries(root: &SysRoot) -> Vec<Battery> {
+    let Ok(entries) = root.read_dir(POWER_SUPPLY_DIR) else {
+        return Vec::new();
+    };
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let attribute = |name: &str| {
+                let value = root
+                    .read_to_string(&format!("{POWER_SUPPLY_DIR}/{}/{name}", entry.name))
+                    .ok()?;
+                dmi::clean(&value)
+            };
+
+            if attribute("type")? != "Battery" || attribute("scope").as_deref() == Some("Device") {
+                return None;
+            }
+
+            Some(Battery {
+                model: attribute("model_name"),
+                capacity: attribute("capacity")?.parse::<u32>().ok()?.min(100),
+                status: attribute("status"),
+                name: entry.name,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,611 @@
+use crate::colors::{self, LogoEntry};
+use crate::layout;
+use crate::style::Style;
+use crate::terminal::Terminal;
+use crate::{Logo, LogoType};
+use glob::Pattern;
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The logos of src/logo/ascii, compiled in by build.rs
+const BUNDLED: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/bundled_logos.rs"));
+
+/// Where the ASCII art of a logo comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogoSource {
+    Bundled(&'static str),
+    File(PathBuf),
+}
+
+/// A logo that can be loaded, named after its file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogoFile {
+    pub name: String,
+    pub source: LogoSource,
+}
+
+impl LogoFile {
+    pub fn load(&self, color_overrides: HashMap<String, Style>) -> Result<Logo, String> {
+        match &self.source {
+            LogoSource::Bundled(content) => {
+                Logo::from_content(&self.name, content, None, color_overrides, LogoType::Text)
+            }
+            LogoSource::File(path) => Logo::from_file(path, color_overrides, LogoType::Text),
+        }
+    }
+
+    /// The front matter of the file, if it has any
+    fn front_matter(&self) -> Option<LogoEntry> {
+        let content = match &self.source {
+            LogoSource::Bundled(content) => content.to_string(),
+            LogoSource::File(path) => {
+                let bytes = fs::File::open(path).and_then(Logo::read_capped).ok()??;
+                String::from_utf8(bytes).ok()?
+            }
+        };
+        colors::parse_front_matter(&content).ok()?.0
+    }
+}
+
+/// Directories searched for logo files, in order, before the bundled logos
+pub fn logo_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+
+    if let Ok(logo_dir) = std::env::var("FASTFETCH_LOGO_DIR") {
+        dirs.push(logo_dir.into());
+    }
+
+    if let Ok(data_home) = std::env::var("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data_home).join("fastfetch-rs/logos"));
+    }
+
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(prefix) = exe_path.parent().and_then(|p| p.parent())
+    {
+        dirs.push(prefix.join("share/fastfetch-rs/logos"));
+    }
+
+    dirs.extend([
+        PathBuf::from("/usr/share/fastfetch-rs/logos"),
+        PathBuf::from("/usr/local/share/fastfetch-rs/logos"),
+    ]);
+
+    if let Ok(home) = std::env::var("HOME") {
+        dirs.push(PathBuf::from(home).join(".local/share/fastfetch-rs/logos"));
+    }
+
+    dirs
+}
+
+/// The logo file called `name`, from the logo directories or bundled
+pub fn find_file(name: &str) -> Option<LogoFile> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return None;
+    }
+
+    let file = logo_dirs()
+        .into_iter()
+        .map(|dir| dir.join(format!("{name}.txt")))
+        .find(|path| path.is_file())
+        .map(LogoSource::File)
+        .or_else(|| {
+            BUNDLED
+                .iter()
+                .find(|(bundled, _)| *bundled == name)
+                .map(|(_, content)| LogoSource::Bundled(content))
+        });
+
+    file.map(|source| LogoFile {
+        name: name.to_string(),
+        source,
+    })
+}
+
+/// The logo selected by `name`: a file of that name, the file of the logo
+/// table entry it names or aliases, or a file declaring it as an alias in
+/// its front matter
+pub fn find(name: &str) -> Option<LogoFile> {
+    let mut names = vec![name.to_string(), name.to_lowercase()];
+    if let Some(entry) = colors::find_logo(name) {
+        names.extend(entry.file_names().map(String::from));
+    }
+
+    names.iter().find_map(|name| find_file(name)).or_else(|| {
+        let alias = name.to_lowercase();
+        available()
+            .into_iter()
+            .filter(|logo| matches!(logo.source, LogoSource::File(_)))
+            .find(|logo| {
+                logo.front_matter()
+                    .is_some_and(|entry| entry.aliases.contains(&alias))
+            })
+    })
+}
+
+/// Every logo, the first file of each name, sorted by name
+pub fn available() -> Vec<LogoFile> {
+    let mut logos: Vec<LogoFile> = Vec::new();
+
+    for dir in logo_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                && path.extension().is_some_and(|ext| ext == "txt")
+                && path.is_file()
+                && !logos.iter().any(|logo| logo.name == name)
+            {
+                logos.push(LogoFile {
+                    name: name.to_string(),
+                    source: LogoSource::File(path.clone()),
+                });
+            }
+        }
+    }
+
+    for (name, content) in BUNDLED {
+        if !logos.iter().any(|logo| logo.name == *name) {
+            logos.push(LogoFile {
+                name: name.to_string(),
+                source: LogoSource::Bundled(content),
+            });
+        }
+    }
+
+    logos.sort_by(|a, b| a.name.cmp(&b.name));
+    logos
+}
+
+/// The logo `name` is a variant of, such as `arch` for `arch_small`,
+/// `arch_old` and `arch2`, when `exists` says there is one
+pub fn variant_of(name: &str, exists: impl Fn(&str) -> bool) -> Option<String> {
+    let mut base = name;
+    loop {
+        let digits = base.trim_end_matches(|c: char| c.is_ascii_digit());
+        let stripped = base
+            .strip_suffix("_small")
+            .or_else(|| base.strip_suffix("_old"))
+            .or_else(|| (digits.len() < base.len()).then_some(digits));
+        match stripped {
+            Some(stripped) if !stripped.is_empty() && !stripped.ends_with('_') => base = stripped,
+            _ => break,
+        }
+    }
+    (base != name && exists(base)).then(|| base.to_string())
+}
+
+/// A logo with the other names that select it and its variants
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listing {
+    pub logo: LogoFile,
+    pub aliases: Vec<String>,
+    pub variants: Vec<LogoFile>,
+}
+
+/// `logos` grouped with their variants, which only get a listing of their
+/// own when `variants` is set
+pub fn listings(logos: Vec<LogoFile>, variants: bool) -> Vec<Listing> {
+    let names: Vec<String> = logos.iter().map(|logo| logo.name.clone()).collect();
+    let base_of = |name: &str| variant_of(name, |base| names.iter().any(|n| n == base));
+
+    let mut listings: Vec<Listing> = logos
+        .iter()
+        .filter(|logo| variants || base_of(&logo.name).is_none())
+        .map(|logo| {
+            let mut aliases = logo.front_matter().unwrap_or_default().aliases;
+            if let Some(entry) = colors::find_logo(&logo.name)
+                && entry.file_names().any(|name| name == logo.name)
+            {
+                aliases.extend(
+                    entry
+                        .file_names()
+                        .filter(|name| *name != logo.name)
+                        .map(String::from),
+                );
+            }
+            aliases.dedup();
+            Listing {
+                logo: logo.clone(),
+                aliases,
+                variants: Vec::new(),
+            }
+        })
+        .collect();
+
+    for logo in &logos {
+        if let Some(base) = base_of(&logo.name)
+            && let Some(listing) = listings.iter_mut().find(|l| l.logo.name == base)
+        {
+            listing.variants.push(logo.clone());
+        }
+    }
+    listings
+}
+
+/// Listings whose name, aliases or variants contain `term`, in any case
+pub fn search(term: &str) -> Vec<Listing> {
+    let term = term.to_lowercase();
+    listings(available(), false)
+        .into_iter()
+        .filter(|listing| {
+            [&listing.logo.name]
+                .into_iter()
+                .chain(&listing.aliases)
+                .chain(listing.variants.iter().map(|variant| &variant.name))
+                .any(|name| name.to_lowercase().contains(&term))
+        })
+        .collect()
+}
+
+/// Print `listings` one per line, with their aliases and variants
+pub fn print_listings(listings: &[Listing], out: &mut impl Write) -> io::Result<()> {
+    let width = listings
+        .iter()
+        .map(|listing| listing.logo.name.len())
+        .max()
+        .unwrap_or(0);
+
+    for listing in listings {
+        let mut details = Vec::new();
+        if !listing.aliases.is_empty() {
+            details.push(format!("aliases: {}", listing.aliases.join(", ")));
+        }
+        if !listing.variants.is_empty() {
+            let names: Vec<&str> = listing.variants.iter().map(|v| v.name.as_str()).collect();
+            details.push(format!("variants: {}", names.join(", ")));
+        }
+        let line = format!("{:<width$}  {}", listing.logo.name, details.join("; "));
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Logos whose name matches the glob `pattern`, when given
+pub fn filter(pattern: Option<&str>) -> Result<Vec<LogoFile>, String> {
+    let pattern = pattern
+        .map(|pattern| Pattern::new(&pattern.to_lowercase()))
+        .transpose()
+        .map_err(|e| format!("Invalid pattern: {e}"))?;
+
+    Ok(available()
+        .into_iter()
+        .filter(|logo| pattern.as_ref().is_none_or(|p| p.matches(&logo.name)))
+        .collect())
+}
+
+/// The logo to use for `--logo name`. `random` picks any available logo,
+/// `daily` one that changes every day (UTC) and differs between hosts;
+/// both take a list of logos to pick from, as in `random:arch,gentoo`.
+/// Logos of the list that are not available are never picked.
+pub fn choose(name: &str, hostname: &str, now: SystemTime) -> Result<String, String> {
+    let (rotation, list) = name.split_once(':').unwrap_or((name, ""));
+    if rotation != "random" && rotation != "daily" {
+        return Ok(name.to_string());
+    }
+
+    let candidates: Vec<String> = if list.trim().is_empty() {
+        listings(available(), false)
+            .into_iter()
+            .map(|listing| listing.logo.name)
+            .collect()
+    } else {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| find(name).is_some())
+            .map(String::from)
+            .collect()
+    };
+    if candidates.is_empty() {
+        return Err(format!("None of the logos of '{name}' is available"));
+    }
+
+    let index = if rotation == "daily" {
+        let day = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / 86_400;
+        fnv1a(hostname.as_bytes()).wrapping_add(day)
+    } else {
+        RandomState::new().hash_one(now)
+    };
+    Ok(candidates[(index % candidates.len() as u64) as usize].clone())
+}
+
+/// A hash that stays the same across runs and builds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The logo piped in on standard input, as in `figlet hi | fastfetch-rs
+/// --logo-file -`
+pub fn read_stdin() -> Result<String, String> {
+    let bytes = Logo::read_capped(io::stdin().lock())
+        .map_err(|e| format!("Failed to read logo from standard input: {e}"))?
+        .ok_or_else(|| {
+            format!(
+                "Logo from standard input too large (max: {} bytes)",
+                Logo::MAX_LOGO_SIZE
+            )
+        })?;
+    String::from_utf8(bytes).map_err(|_| "Logo from standard input is not valid UTF-8".to_string())
+}
+
+/// The logo of `--logo-inline`: a `data:` URI, or text in which `\n`
+/// starts a new line and `\\` is a backslash. Text that already spans
+/// several lines, such as `"$(figlet hi)"`, is taken as it is.
+pub fn inline_text(text: &str) -> Result<String, String> {
+    if let Some(uri) = text.strip_prefix("data:") {
+        let (media_type, data) = uri
+            .split_once(',')
+            .ok_or("Invalid data URI: no `,` before the data")?;
+        let bytes = if media_type.ends_with(";base64") {
+            decode_base64(data).ok_or("Invalid data URI: bad base64")?
+        } else {
+            percent_decode(data).ok_or("Invalid data URI: bad `%` escape")?
+        };
+        return String::from_utf8(bytes).map_err(|_| "Logo is not valid UTF-8".to_string());
+    }
+
+    if text.contains('\n') {
+        return Ok(text.to_string());
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                unescaped.push('\n');
+            }
+            ('\\', Some('\\')) => {
+                chars.next();
+                unescaped.push('\\');
+            }
+            _ => unescaped.push(ch),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Standard or URL-safe base64, padded or not
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for byte in text
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b'=')
+    {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6 | value as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// `text` with its `%XX` escapes decoded
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+        let hex = tail
+            .get(..2)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+        bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+        rest = &tail[2..];
+    }
+    Some(bytes)
+}
+
+/// Columns between logos in the gallery
+const GALLERY_GAP: usize = 4;
+
+/// Draw `logos` side by side, as many as fit in a row, each under its
+/// name. On a terminal, stop after each screenful until Enter is pressed;
+/// `q` quits.
+pub fn gallery(logos: &[LogoFile], terminal: &Terminal, out: &mut impl Write) -> io::Result<()> {
+    let width = terminal.width.unwrap_or(120);
+    let mut printed = 0;
+    let mut input = io::stdin().lock();
+
+    let mut cells = logos.iter().filter_map(|file| {
+        let logo = file.load(HashMap::new()).ok()?;
+        let logo_width = logo.width().max(layout::text_width(&file.name));
+        let mut lines = vec![format!(
+            "\x1b[1m{}\x1b[0m{}",
+            file.name,
+            layout::padding(layout::text_width(&file.name), logo_width)
+        )];
+        lines.extend(
+            logo.rendered_lines()
+                .into_iter()
+                .map(|line| layout::truncate(&line, width)),
+        );
+        Some((lines, logo_width.min(width)))
+    });
+
+    let mut next = cells.next();
+    while next.is_some() {
+        let mut row: Vec<(Vec<String>, usize)> = Vec::new();
+        let mut used = 0;
+        while let Some((lines, cell_width)) = next.take() {
+            if !row.is_empty() && used + GALLERY_GAP + cell_width > width {
+                next = Some((lines, cell_width));
+                break;
+            }
+            used += cell_width + if row.is_empty() { 0 } else { GALLERY_GAP };
+            row.push((lines, cell_width));
+            next = cells.next();
+        }
+
+        let height = row.iter().map(|(lines, _)| lines.len()).max().unwrap_or(0);
+        if let Some(rows) = terminal.height
+            && printed > 0
+            && printed + height + 1 > rows
+        {
+            write!(out, "-- more (Enter, q to quit) --")?;
+            out.flush()?;
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 || answer.trim() == "q" {
+                return Ok(());
+            }
+            printed = 0;
+        }
+
+        let mut text = String::new();
+        for i in 0..height {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|(lines, cell_width)| match lines.get(i) {
+                    Some(line) => {
+                        format!(
+                            "{line}{}",
+                            layout::padding(layout::text_width(line), *cell_width)
+                        )
+                    }
+                    None => " ".repeat(*cell_width),
+                })
+                .collect();
+            text.push_str(cells.join(&" ".repeat(GALLERY_GAP)).trim_end());
+            text.push('\n');
+        }
+        text.push('\n');
+        write!(out, "{}", terminal.adapt(&text))?;
+        printed += height + 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants() {
+        let exists = |name: &str| ["arch", "ubuntu", "alpine", "windows"].contains(&name);
+        assert_eq!(variant_of("arch_small", exists).as_deref(), Some("arch"));
+        assert_eq!(variant_of("arch2", exists).as_deref(), Some("arch"));
+        assert_eq!(
+            variant_of("ubuntu_old2_small", exists).as_deref(),
+            Some("ubuntu")
+        );
+        assert_eq!(
+            variant_of("alpine2_small", exists).as_deref(),
+            Some("alpine")
+        );
+        assert_eq!(variant_of("windows_11", exists), None);
+        assert_eq!(variant_of("arch", exists), None);
+        assert_eq!(variant_of("gentoo_small", exists), None);
+    }
+
+    #[test]
+    fn test_choose() {
+        let day = |n: u64| UNIX_EPOCH + std::time::Duration::from_secs(n * 86_400 + 3600);
+        let list = "daily:arch,gentoo,nixos";
+
+        assert_eq!(choose("arch", "host", day(1)).unwrap(), "arch");
+        assert_eq!(
+            choose(list, "host", day(1)).unwrap(),
+            choose(list, "host", day(1) + std::time::Duration::from_secs(3600)).unwrap()
+        );
+        let days: Vec<String> = (0..3)
+            .map(|n| choose(list, "host", day(n)).unwrap())
+            .collect();
+        assert!(days.contains(&"arch".to_string()) && days.contains(&"nixos".to_string()));
+        // Hosts start the rotation at different logos
+        let hosts: Vec<String> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|host| choose(list, host, day(1)).unwrap())
+            .collect();
+        assert!(hosts.iter().any(|pick| *pick != hosts[0]));
+
+        for _ in 0..20 {
+            let pick = choose("random:arch,no-such-logo,gentoo", "host", SystemTime::now());
+            assert!(["arch", "gentoo"].contains(&pick.unwrap().as_str()));
+        }
+        assert!(find(&choose("random", "host", SystemTime::now()).unwrap()).is_some());
+        assert!(choose("random:no-such-logo", "host", SystemTime::now()).is_err());
+    }
+
+    #[test]
+    fn test_inline_text() {
+        assert_eq!(inline_text(r"$1 /\\\n$2/__\\").unwrap(), "$1 /\\\n$2/__\\");
+        assert_eq!(inline_text(r"a\tb\").unwrap(), r"a\tb\");
+        // Command output keeps its backslashes
+        assert_eq!(inline_text(" /\\n\n/__\\").unwrap(), " /\\n\n/__\\");
+
+        assert_eq!(inline_text("data:,%241%20/%5C%0A").unwrap(), "$1 /\\\n");
+        assert_eq!(
+            inline_text("data:text/plain;base64,JDEgL1wKJDIvX19c").unwrap(),
+            "$1 /\\\n$2/__\\"
+        );
+        assert_eq!(inline_text("data:;base64,aGk").unwrap(), "hi");
+        assert!(inline_text("data:hi").is_err());
+        assert!(inline_text("data:,%2").is_err());
+        assert!(inline_text("data:,%+1").is_err());
+        assert!(inline_text("data:;base64,a!").is_err());
+        assert!(inline_text("data:;base64,/w==").is_err());
+    }
+
+    #[test]
+    fn test_bundled_catalog() {
+        let arch = find("ArchLinux").unwrap();
+        assert_eq!(arch.name, "arch");
+        assert_eq!(find("pop!_os").unwrap().name, "pop");
+        assert!(find("../arch").is_none());
+        assert!(find("no-such-logo").is_none());
+
+        let listings = listings(filter(Some("arch*")).unwrap(), false);
+        let arch = listings.iter().find(|l| l.logo.name == "arch").unwrap();
+        assert!(arch.aliases.contains(&"archlinux".to_string()));
+        let variants: Vec<&str> = arch.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(variants, ["arch2", "arch3", "arch_old", "arch_small"]);
+        assert!(!listings.iter().any(|l| l.logo.name == "arch_small"));
+
+        assert!(search("ARCHLINUX").iter().any(|l| l.logo.name == "arch"));
+        assert!(search("nixos_old").iter().any(|l| l.logo.name == "nixos"));
+        assert!(filter(Some("[")).is_err());
+    }
+
+    #[test]
+    fn test_gallery() {
+        let logo = |name: &str, art: &'static str| LogoFile {
+            name: name.to_string(),
+            source: LogoSource::Bundled(art),
+        };
+        let logos = [logo("a_long_name", "$1x\n$1y\n"), logo("b", "$1zz\n")];
+
+        let mut out = Vec::new();
+        gallery(&logos, &Terminal::default(), &mut out).unwrap();
+        let lines: Vec<String> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(layout::visible_text)
+            .collect();
+
+        // Logos stay under their names when a name is wider than its logo
+        assert_eq!(lines, ["a_long_name    b", "x              zz", "y", ""]);
+    }
+}
@@ -0,0 +1,551 @@
+use crate::sysroot::SysRoot;
+use glob::Pattern;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Filesystem types that never describe real storage.
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fuse.gvfsd-fuse",
+    "fuse.lxcfs",
+    "fuse.portal",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nfsd",
+    "nsfs",
+    "overlay",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+/// Mount points hidden unless the user asks for them with `--disk-include`.
+const DEFAULT_EXCLUDED_MOUNTS: &[&str] = &[
+    "/boot",
+    "/boot/efi",
+    "/efi",
+    "/dev",
+    "/dev/*",
+    "/proc",
+    "/proc/*",
+    "/sys",
+    "/sys/*",
+    "/run",
+    "/run/*",
+    "/tmp",
+    "/snap/*",
+    "/var/lib/docker/*",
+    "/var/lib/containers/*",
+    "*/.zfs/snapshot/*",
+];
+
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "ceph",
+    "9p",
+    "afs",
+    "fuse.sshfs",
+    "fuse.glusterfs",
+    "fuse.davfs",
+];
+
+/// statvfs on a dead network server can block forever, so give up after this.
+const NETWORK_STATFS_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub enum DiskPattern {
+    MountPoint(Pattern),
+    FsType(Pattern),
+    Device(Pattern),
+}
+
+impl DiskPattern {
+    /// Parse a filter pattern: `fs:<glob>` matches the filesystem type,
+    /// `dev:<glob>` the mount source, anything else (optionally prefixed with
+    /// `mount:`) the mount point.
+    pub fn parse(spec: &str) -> Option<Self> {
+        if let Some(glob) = spec.strip_prefix("fs:") {
+            Pattern::new(glob).ok().map(DiskPattern::FsType)
+        } else if let Some(glob) = spec.strip_prefix("dev:") {
+            Pattern::new(glob).ok().map(DiskPattern::Device)
+        } else {
+            let glob = spec.strip_prefix("mount:").unwrap_or(spec);
+            Pattern::new(glob).ok().map(DiskPattern::MountPoint)
+        }
+    }
+
+    fn matches(&self, mount: &MountEntry) -> bool {
+        match self {
+            DiskPattern::MountPoint(p) => p.matches(&mount.mount_point),
+            DiskPattern::FsType(p) => p.matches(&mount.fs_type),
+            DiskPattern::Device(p) => p.matches(&mount.source),
+        }
+    }
+}
+
+/// Decides which mounts are listed. Explicit excludes always win; explicit
+/// includes replace the built-in exclusion list.
+#[derive(Debug, Clone, Default)]
+pub struct DiskFilter {
+    pub include: Vec<DiskPattern>,
+    pub exclude: Vec<DiskPattern>,
+}
+
+impl DiskFilter {
+    fn allows(&self, mount: &MountEntry) -> bool {
+        if self.exclude.iter().any(|p| p.matches(mount)) {
+            return false;
+        }
+
+        if !self.include.is_empty() {
+            return self.include.iter().any(|p| p.matches(mount));
+        }
+
+        !PSEUDO_FS_TYPES.contains(&mount.fs_type.as_str())
+            && !DEFAULT_EXCLUDED_MOUNTS
+                .iter()
+                .any(|p| Pattern::new(p).is_ok_and(|p| p.matches(&mount.mount_point)))
+    }
+}
+
+/// One line of `/proc/self/mountinfo`.
+#[derive(Debug, Clone, PartialEq)]
+struct MountEntry {
+    dev_id: String,
+    root: String,
+    mount_point: String,
+    fs_type: String,
+    source: String,
+    mount_options: String,
+    super_options: String,
+}
+
+impl MountEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let (left, right) = line.split_once(" - ")?;
+        let left: Vec<&str> = left.split(' ').collect();
+        let right: Vec<&str> = right.split(' ').collect();
+        if left.len() < 6 || right.len() < 2 {
+            return None;
+        }
+
+        Some(MountEntry {
+            dev_id: left[2].to_string(),
+            root: unescape_mount_field(left[3]),
+            mount_point: unescape_mount_field(left[4]),
+            mount_options: left[5].to_string(),
+            fs_type: right[0].to_string(),
+            source: unescape_mount_field(right[1]),
+            super_options: right.get(2).unwrap_or(&"").to_string(),
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.mount_options.split(',').any(|opt| opt == "ro")
+    }
+
+    fn is_network(&self) -> bool {
+        NETWORK_FS_TYPES.contains(&self.fs_type.as_str())
+    }
+
+    /// The subvolume a btrfs mount points at, e.g. `/@home`.
+    fn btrfs_subvolume(&self) -> Option<&str> {
+        if self.fs_type != "btrfs" {
+            return None;
+        }
+        self.super_options
+            .split(',')
+            .find_map(|opt| opt.strip_prefix("subvol="))
+    }
+
+    /// Bind mounts expose a subdirectory of a filesystem that is already
+    /// mounted elsewhere. btrfs and ZFS report their subvolume/dataset root
+    /// here, which is not a bind mount.
+    fn is_bind_mount(&self) -> bool {
+        match self.btrfs_subvolume() {
+            Some(subvol) => self.root != subvol,
+            None => self.root != "/",
+        }
+    }
+}
+
+/// mountinfo escapes space, tab, newline and backslash as `\ooo`.
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && i + 3 < bytes.len()
+            && bytes[i + 1..i + 4]
+                .iter()
+                .all(|b| (b'0'..=b'7').contains(b))
+        {
+            let value = bytes[i + 1..i + 4]
+                .iter()
+                .fold(0u32, |acc, b| acc * 8 + (b - b'0') as u32);
+            out.push(value as u8);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Physical properties of the block device backing a mount.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockDevice {
+    pub name: String,
+    pub model: Option<String>,
+    pub size: u64,
+    pub rotational: bool,
+    pub removable: bool,
+}
+
+/// Raw `statvfs` counters. Block counts are in units of `fragment_size`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FsStats {
+    pub fragment_size: u64,
+    pub blocks: u64,
+    pub blocks_free: u64,
+    pub blocks_available: u64,
+    pub files: u64,
+    pub files_free: u64,
+}
+
+/// Space and inode usage computed the way `df` does it: blocks reserved for
+/// root count neither as used nor as available, and percentages round up.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DiskUsage {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub percent: u32,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inodes_percent: u32,
+}
+
+impl DiskUsage {
+    pub fn from_stats(stats: &FsStats) -> Self {
+        let used_blocks = stats.blocks.saturating_sub(stats.blocks_free);
+        let inodes_used = stats.files.saturating_sub(stats.files_free);
+
+        DiskUsage {
+            total: stats.blocks * stats.fragment_size,
+            used: used_blocks * stats.fragment_size,
+            available: stats.blocks_available * stats.fragment_size,
+            percent: percent_ceil(used_blocks, used_blocks + stats.blocks_available),
+            inodes_total: stats.files,
+            inodes_used,
+            inodes_percent: percent_ceil(inodes_used, stats.files),
+        }
+    }
+}
+
+fn percent_ceil(part: u64, whole: u64) -> u32 {
+    if whole == 0 {
+        return 0;
+    }
+    (part as u128 * 100).div_ceil(whole as u128) as u32
+}
+
+#[derive(Debug, Clone)]
+pub struct Disk {
+    pub mount_point: String,
+    pub source: String,
+    pub fs_type: String,
+    pub subvolume: Option<String>,
+    pub network: bool,
+    pub read_only: bool,
+    pub usage: DiskUsage,
+    pub device: Option<BlockDevice>,
+}
+
+pub fn detect_disks(root: &SysRoot, filter: &DiskFilter) -> Vec<Disk> {
+    let Ok(mountinfo) = root.read_to_string("/proc/self/mountinfo") else {
+        return Vec::new();
+    };
+
+    let mut disks = Vec::new();
+    let mut seen = HashSet::new();
+
+    for mount in select_mounts(&mountinfo, filter) {
+        let subvolume = mount.btrfs_subvolume().map(|s| s.to_string());
+
+        if !seen.insert((mount.dev_id.clone(), mount.root.clone())) {
+            continue;
+        }
+
+        let network = mount.is_network();
+        let timeout = network.then_some(NETWORK_STATFS_TIMEOUT);
+        let stats = root.statvfs(&mount.mount_point, timeout);
+
+        let Some(stats) = stats else {
+            continue;
+        };
+
+        let device = if network {
+            None
+        } else {
+            block_device_for(root, &mount.dev_id, &mount.source)
+        };
+
+        disks.push(Disk {
+            read_only: mount.is_read_only(),
+            mount_point: mount.mount_point,
+            source: mount.source,
+            fs_type: mount.fs_type,
+            subvolume,
+            network,
+            usage: DiskUsage::from_stats(&stats),
+            device,
+        });
+    }
+
+    disks.sort_by(|a, b| {
+        if a.mount_point == "/" {
+            std::cmp::Ordering::Less
+        } else if b.mount_point == "/" {
+            std::cmp::Ordering::Greater
+        } else {
+            a.mount_point.cmp(&b.mount_point)
+        }
+    });
+
+    disks
+}
+
+fn select_mounts(mountinfo: &str, filter: &DiskFilter) -> Vec<MountEntry> {
+    mountinfo
+        .lines()
+        .filter_map(MountEntry::parse)
+        .filter(|m| !m.is_bind_mount() && filter.allows(m))
+        .collect()
+}
+
+/// Find the whole-disk entry in `/sys/block` for a mount, following
+/// partitions up to their parent and device-mapper targets down to the
+/// first underlying device.
+fn block_device_for(root: &SysRoot, dev_id: &str, source: &str) -> Option<BlockDevice> {
+    let mut sys_path = root.canonicalize(&format!("/sys/dev/block/{dev_id}")).ok();
+
+    if sys_path.is_none() && source.starts_with("/dev/") {
+        let resolved = root.canonicalize(source).ok()?;
+        let name = resolved.rsplit('/').next()?;
+        sys_path = root.canonicalize(&format!("/sys/class/block/{name}")).ok();
+    }
+
+    let mut sys_path = sys_path?;
+
+    for _ in 0..8 {
+        let slaves_dir = format!("{sys_path}/slaves");
+        let first_slave = root
+            .read_dir(&slaves_dir)
+            .ok()
+            .and_then(|entries| entries.into_iter().next())
+            .and_then(|e| root.canonicalize(&format!("{slaves_dir}/{}", e.name)).ok());
+
+        match first_slave {
+            Some(slave) => sys_path = slave,
+            None => break,
+        }
+    }
+
+    if root.exists(&format!("{sys_path}/partition")) {
+        sys_path = sys_path.rsplit_once('/')?.0.to_string();
+    }
+
+    read_block_device(root, &sys_path)
+}
+
+fn read_block_device(root: &SysRoot, sys_path: &str) -> Option<BlockDevice> {
+    let read = |name: &str| {
+        root.read_to_string(&format!("{sys_path}/{name}"))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let name = sys_path.rsplit('/').next()?.to_string();
+    let sectors: u64 = read("size")?.parse().ok()?;
+
+    Some(BlockDevice {
+        name,
+        model: read("device/model").or_else(|| read("device/name")),
+        size: sectors * 512,
+        rotational: read("queue/rotational").is_some_and(|v| v == "1"),
+        removable: read("removable").is_some_and(|v| v == "1"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 259:2 / / rw,relatime - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid - proc proc rw
+24 22 0:22 / /sys rw,nosuid - sysfs sysfs rw
+25 22 0:23 / /run rw,nosuid - tmpfs tmpfs rw,mode=755
+26 22 259:1 / /boot rw,relatime - vfat /dev/nvme0n1p1 rw
+27 22 0:35 /@home /home rw,relatime - btrfs /dev/sda1 rw,subvol=/@home
+28 22 0:35 /@data /srv/data rw,relatime - btrfs /dev/sda1 rw,subvol=/@data
+29 22 0:36 / /tank/media rw,xattr - zfs tank/media rw
+30 22 0:37 / /mnt/nas rw,relatime - nfs4 nas.lan:/export rw,vers=4.2
+31 22 0:38 / /mnt/share rw,relatime - cifs //fileserver/share rw
+32 22 259:2 /nix/store /nix/store ro,relatime - ext4 /dev/nvme0n1p2 rw
+33 22 0:35 /@home/alice/www /var/www rw,relatime - btrfs /dev/sda1 rw,subvol=/@home
+34 22 7:0 / /snap/core/1 ro - squashfs /dev/loop0 ro
+35 22 259:3 / /mnt/my\\040disk rw - ext4 /dev/nvme0n1p3 rw
+";
+
+    fn mount_points(filter: &DiskFilter) -> Vec<String> {
+        select_mounts(MOUNTINFO, filter)
+            .into_iter()
+            .map(|m| m.mount_point)
+            .collect()
+    }
+
+    #[test]
+    fn test_default_filter() {
+        assert_eq!(
+            mount_points(&DiskFilter::default()),
+            vec![
+                "/",
+                "/home",
+                "/srv/data",
+                "/tank/media",
+                "/mnt/nas",
+                "/mnt/share",
+                "/mnt/my disk",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_mounts_hidden() {
+        let filter = DiskFilter {
+            include: vec![DiskPattern::parse("*").unwrap()],
+            exclude: vec![],
+        };
+        let mounts = mount_points(&filter);
+        assert!(!mounts.contains(&"/nix/store".to_string()));
+        assert!(!mounts.contains(&"/var/www".to_string()));
+        assert!(mounts.contains(&"/boot".to_string()));
+    }
+
+    #[test]
+    fn test_include_and_exclude_patterns() {
+        let filter = DiskFilter {
+            include: vec![
+                DiskPattern::parse("fs:btrfs").unwrap(),
+                DiskPattern::parse("dev:tank/*").unwrap(),
+            ],
+            exclude: vec![DiskPattern::parse("/srv/*").unwrap()],
+        };
+        assert_eq!(mount_points(&filter), vec!["/home", "/tank/media"]);
+
+        let filter = DiskFilter {
+            include: vec![],
+            exclude: vec![
+                DiskPattern::parse("fs:nfs*").unwrap(),
+                DiskPattern::parse("mount:/mnt/share").unwrap(),
+            ],
+        };
+        let mounts = mount_points(&filter);
+        assert!(!mounts.contains(&"/mnt/nas".to_string()));
+        assert!(!mounts.contains(&"/mnt/share".to_string()));
+        assert!(mounts.contains(&"/".to_string()));
+    }
+
+    #[test]
+    fn test_mount_entry_parse() {
+        let entry = MountEntry::parse(MOUNTINFO.lines().nth(5).unwrap()).unwrap();
+        assert_eq!(entry.dev_id, "0:35");
+        assert_eq!(entry.btrfs_subvolume(), Some("/@home"));
+        assert!(!entry.is_bind_mount());
+
+        let nfs = MountEntry::parse(MOUNTINFO.lines().nth(8).unwrap()).unwrap();
+        assert!(nfs.is_network());
+        assert_eq!(nfs.source, "nas.lan:/export");
+    }
+
+    #[test]
+    fn test_read_only_flag() {
+        let entries: Vec<MountEntry> = MOUNTINFO.lines().filter_map(MountEntry::parse).collect();
+        assert!(!entries[0].is_read_only());
+        assert!(entries[10].is_read_only());
+    }
+
+    #[test]
+    fn test_usage_matches_df() {
+        // 5% of the blocks are reserved for root, `df` reports Use% 74%
+        let stats = FsStats {
+            fragment_size: 4096,
+            blocks: 1_000_000,
+            blocks_free: 300_000,
+            blocks_available: 250_000,
+            files: 262_144,
+            files_free: 200_000,
+        };
+        let usage = DiskUsage::from_stats(&stats);
+        assert_eq!(usage.total, 4_096_000_000);
+        assert_eq!(usage.used, 2_867_200_000);
+        assert_eq!(usage.available, 1_024_000_000);
+        assert_eq!(usage.percent, 74);
+        assert_eq!(usage.inodes_used, 62_144);
+        assert_eq!(usage.inodes_percent, 24);
+    }
+
+    #[test]
+    fn test_usage_edge_cases() {
+        assert_eq!(
+            DiskUsage::from_stats(&FsStats::default()),
+            DiskUsage::default()
+        );
+
+        let full = FsStats {
+            fragment_size: 512,
+            blocks: 100,
+            blocks_free: 5,
+            blocks_available: 0,
+            files: 0,
+            files_free: 0,
+        };
+        let usage = DiskUsage::from_stats(&full);
+        assert_eq!(usage.percent, 100);
+        assert_eq!(usage.inodes_percent, 0);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(DiskPattern::parse("[").is_none());
+        assert!(DiskPattern::parse("fs:[").is_none());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::smbios::{self, Structure};
+use crate::sysroot::SysRoot;
+
+const DMI_ID_DIR: &str = "/sys/devices/virtual/dmi/id";
+
+/// Device-tree model of boards without DMI (ARM, RISC-V)
+const DEVICE_TREE_MODELS: &[&str] = &[
+    "/proc/device-tree/model",
+    "/sys/firmware/devicetree/base/model",
+];
+
+/// Values firmware vendors leave in fields they did not fill in.
+const PLACEHOLDER_VALUES: &[&str] = &[
+    "to be filled by o.e.m.",
+    "to be filled by oem",
+    "default string",
+    "system manufacturer",
+    "system product name",
+    "system version",
+    "system serial number",
+    "system sku",
+    "base board manufacturer",
+    "base board product name",
+    "chassis manufacturer",
+    "chassis version",
+    "not applicable",
+    "not specified",
+    "not available",
+    "none",
+    "n/a",
+    "o.e.m.",
+    "oem",
+    "unknown",
+    "default",
+    "type1productconfigid",
+    "type2 - board vendor name1",
+    "type2 - board product name1",
+    "0123456789",
+    "123456789",
+    "x.x",
+];
+
+const SMBIOS_BIOS: u8 = 0;
+const SMBIOS_SYSTEM: u8 = 1;
+const SMBIOS_BASEBOARD: u8 = 2;
+const SMBIOS_CHASSIS: u8 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bios {
+    pub vendor: Option<String>,
+    pub version: Option<String>,
+    pub date: Option<String>,
+    /// Booted through UEFI rather than legacy BIOS
+    pub uefi: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Product {
+    pub vendor: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub family: Option<String>,
+    pub sku: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Board {
+    pub vendor: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chassis {
+    pub vendor: Option<String>,
+    pub kind: Option<&'static str>,
+    pub version: Option<String>,
+}
+
+/// Firmware-provided identity of the machine. Fields come from the kernel's
+/// `dmi/id` attributes, with the raw SMBIOS table filling in the ones it
+/// does not export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dmi {
+    pub bios: Bios,
+    pub product: Product,
+    pub board: Board,
+    pub chassis: Chassis,
+}
+
+impl Dmi {
+    pub fn detect(root: &SysRoot) -> Self {
+        let table = smbios::read_table(root);
+        let structure = |kind: u8| table.iter().find(|s| s.kind == kind);
+
+        let field = |name: &str, kind: u8, offset: usize| {
+            read_field(root, name).or_else(|| table_string(structure(kind), offset))
+        };
+
+        let chassis_kind = read_field(root, "chassis_type")
+            .and_then(|code| code.parse::<u8>().ok())
+            .or_else(|| structure(SMBIOS_CHASSIS)?.byte(0x05))
+            .and_then(chassis_type_name);
+
+        Dmi {
+            bios: Bios {
+                vendor: field("bios_vendor", SMBIOS_BIOS, 0x04),
+                version: field("bios_version", SMBIOS_BIOS, 0x05),
+                date: field("bios_date", SMBIOS_BIOS, 0x08),
+                uefi: root.is_dir("/sys/firmware/efi"),
+            },
+            product: Product {
+                vendor: field("sys_vendor", SMBIOS_SYSTEM, 0x04),
+                name: field("product_name", SMBIOS_SYSTEM, 0x05),
+                version: field("product_version", SMBIOS_SYSTEM, 0x06),
+                sku: field("product_sku", SMBIOS_SYSTEM, 0x19),
+                family: field("product_family", SMBIOS_SYSTEM, 0x1A),
+            },
+            board: Board {
+                vendor: field("board_vendor", SMBIOS_BASEBOARD, 0x04),
+                name: field("board_name", SMBIOS_BASEBOARD, 0x05),
+                version: field("board_version", SMBIOS_BASEBOARD, 0x06),
+            },
+            chassis: Chassis {
+                vendor: field("chassis_vendor", SMBIOS_CHASSIS, 0x04),
+                kind: chassis_kind,
+                version: field("chassis_version", SMBIOS_CHASSIS, 0x06),
+            },
+        }
+    }
+}
+
+fn read_field(root: &SysRoot, name: &str) -> Option<String> {
+    let value = root.read_to_string(&format!("{DMI_ID_DIR}/{name}")).ok()?;
+    clean(&value)
+}
+
+fn table_string(structure: Option<&Structure>, offset: usize) -> Option<String> {
+    clean(structure?.string(offset)?)
+}
+
+/// Trimmed value, or `None` when blank or a vendor placeholder
+pub fn clean(value: &str) -> Option<String> {
+    let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    if value.is_empty() || PLACEHOLDER_VALUES.contains(&value.to_lowercase().as_str()) {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+/// Board model from the device tree, for machines without DMI
+pub fn device_tree_model(root: &SysRoot) -> Option<String> {
+    DEVICE_TREE_MODELS
+        .iter()
+        .find_map(|path| clean(&root.read_to_string(path).ok()?))
+}
+
+/// SMBIOS chassis types; bit 7 of the code is the chassis lock flag
+fn chassis_type_name(code: u8) -> Option<&'static str> {
+    Some(match code & 0x7F {
+        1 => "Other",
+        3 => "Desktop",
+        4 => "Low Profile Desktop",
+        5 => "Pizza Box",
+        6 => "Mini Tower",
+        7 => "Tower",
+        8 => "Portable",
+        9 => "Laptop",
+        10 => "Notebook",
+        11 => "Hand Held",
+        12 => "Docking Station",
+        13 => "All in One",
+        14 => "Sub Notebook",
+        15 => "Space-saving",
+        16 => "Lunch Box",
+        17 => "Main Server Chassis",
+        18 => "Expansion Chassis",
+        19 => "SubChassis",
+        20 => "Bus Expansion Chassis",
+        21 => "Peripheral Chassis",
+        22 => "RAID Chassis",
+        23 => "Rack Mount Chassis",
+        24 => "Sealed-case PC",
+        25 => "Multi-system Chassis",
+        26 => "Compact PCI",
+        27 => "Advanced TCA",
+        28 => "Blade",
+        29 => "Blade Enclosure",
+        30 => "Tablet",
+        31 => "Convertible",
+        32 => "Detachable",
+        33 => "IoT Gateway",
+        34 => "Embedded PC",
+        35 => "Mini PC",
+        36 => "Stick PC",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_placeholders() {
+        assert_eq!(clean("To be filled by O.E.M.\n"), None);
+        assert_eq!(clean("Default string"), None);
+        assert_eq!(clean("  \n"), None);
+        assert_eq!(clean("System Product Name"), None);
+        assert_eq!(
+            clean("Raspberry Pi 4 Model B Rev 1.4\0"),
+            Some("Raspberry Pi 4 Model B Rev 1.4".to_string())
+        );
+        assert_eq!(clean(" LENOVO \n"), Some("LENOVO".to_string()));
+    }
+
+    #[test]
+    fn test_chassis_type_name() {
+        assert_eq!(chassis_type_name(3), Some("Desktop"));
+        assert_eq!(chassis_type_name(0x80 | 10), Some("Notebook"));
+        assert_eq!(chassis_type_name(2), None);
+        assert_eq!(chassis_type_name(99), None);
+    }
+}
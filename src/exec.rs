@@ -0,0 +1,194 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Directories searched for programs given by name. The user's `PATH` is
+/// not used, so a writable directory early in it cannot shadow `rpm` or `ip`.
+const TRUSTED_PATH: &[&str] = &[
+    "/usr/local/sbin",
+    "/usr/local/bin",
+    "/usr/sbin",
+    "/usr/bin",
+    "/sbin",
+    "/bin",
+    "/run/current-system/sw/bin",
+    "/nix/var/nix/profiles/default/bin",
+];
+
+/// The only variables passed on: what display tools need to reach the
+/// session. Everything else, `LD_PRELOAD` included, is dropped.
+const PASSED_ENV: &[&str] = &[
+    "HOME",
+    "DISPLAY",
+    "XAUTHORITY",
+    "WAYLAND_DISPLAY",
+    "XDG_RUNTIME_DIR",
+];
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_MAX_OUTPUT: usize = 4 * 1024 * 1024;
+
+/// Bounds on a single command
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub timeout: Duration,
+    /// Commands printing more than this many bytes are treated as failed
+    pub max_output: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            timeout: DEFAULT_TIMEOUT,
+            max_output: DEFAULT_MAX_OUTPUT,
+        }
+    }
+}
+
+/// Standard output of a command that exited successfully within the limits.
+/// It runs with a cleared environment, `/dev/null` on stdin and stderr, and
+/// is killed when it takes too long.
+pub fn run(program: &str, args: &[&str], limits: &Limits) -> Option<String> {
+    let executable = resolve(program)?;
+
+    let mut command = Command::new(&executable);
+    command
+        .args(args)
+        .env_clear()
+        .env("PATH", TRUSTED_PATH.join(":"))
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    for key in PASSED_ENV {
+        if let Some(value) = env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+
+    let mut child = command.spawn().ok()?;
+    let deadline = Instant::now() + limits.timeout;
+
+    // Read on a helper thread: a child that hangs without closing stdout,
+    // or leaves a grandchild holding it, must not block us
+    let mut stdout = child.stdout.take()?;
+    let max_output = limits.max_output;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let result = (&mut stdout)
+            .take(max_output as u64 + 1)
+            .read_to_end(&mut output);
+        let _ = tx.send(result.map(|_| output));
+    });
+
+    let output = rx.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+    let status = match output {
+        Ok(Ok(_)) => wait_until(&mut child, deadline),
+        _ => None,
+    };
+    let Some(status) = status else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+    };
+
+    let output = output.ok()?.ok()?;
+    if !status.success() || output.len() > max_output {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output).to_string())
+}
+
+fn wait_until(
+    child: &mut std::process::Child,
+    deadline: Instant,
+) -> Option<std::process::ExitStatus> {
+    loop {
+        if let Some(status) = child.try_wait().ok()? {
+            return Some(status);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Absolute paths are used as given, names are looked up in the trusted
+/// directories. World-writable executables are refused either way.
+fn resolve(program: &str) -> Option<PathBuf> {
+    let candidates: Vec<PathBuf> = if program.contains('/') {
+        let path = Path::new(program);
+        if !path.is_absolute() {
+            return None;
+        }
+        vec![path.to_path_buf()]
+    } else {
+        TRUSTED_PATH
+            .iter()
+            .map(|dir| Path::new(dir).join(program))
+            .collect()
+    };
+
+    candidates.into_iter().find(|path| {
+        fs::metadata(path).is_ok_and(|meta| {
+            let mode = meta.permissions().mode();
+            meta.is_file() && mode & 0o111 != 0 && mode & 0o002 == 0
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_clears_environment() {
+        let output = run(
+            "sh",
+            &["-c", "echo \"$PATH|$LD_PRELOAD|$LC_ALL\""],
+            &Limits::default(),
+        );
+        let expected = format!("{}||C\n", TRUSTED_PATH.join(":"));
+        assert_eq!(output, Some(expected));
+
+        // stdin is /dev/null, so this returns at once
+        assert_eq!(
+            run("sh", &["-c", "cat; echo done"], &Limits::default()),
+            Some("done\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_run_limits() {
+        let limits = Limits {
+            timeout: Duration::from_millis(200),
+            max_output: 16,
+        };
+
+        let start = Instant::now();
+        assert_eq!(run("sh", &["-c", "sleep 5"], &limits), None);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        assert_eq!(run("sh", &["-c", "echo 0123456789abcdef"], &limits), None);
+        assert_eq!(
+            run("sh", &["-c", "echo 0123456789"], &limits),
+            Some("0123456789\n".to_string())
+        );
+        assert_eq!(run("sh", &["-c", "exit 1"], &limits), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert!(resolve("sh").is_some());
+        assert_eq!(resolve("bin/sh"), None);
+        assert_eq!(resolve("definitely-not-a-program"), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::iter::Peekable;
+use std::str::Chars;
+use unicode_width::UnicodeWidthStr;
+
+/// Terminal tab stops, used to expand tabs in logos
+const TAB_WIDTH: usize = 8;
+
+/// Number of terminal columns `text` takes. East Asian wide characters and
+/// emoji count as two, combining marks and zero-width joiners inside emoji
+/// sequences as nothing. Escape sequences and other control characters take
+/// no space.
+pub fn text_width(text: &str) -> usize {
+    visible_text(text).width()
+}
+
+/// `text` without escape sequences and control characters
+pub fn visible_text(text: &str) -> String {
+    strip_escapes(text)
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+/// `text` without escape sequences; newlines and tabs are kept
+pub fn strip_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            escape_sequence(&mut chars);
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// `text` with only the escape sequences that set colors or attributes
+/// (SGR): cursor movement, OSC and other sequences are removed, as are
+/// control characters other than newlines and tabs
+pub fn keep_sgr(text: &str) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            let sequence = escape_sequence(&mut chars);
+            if sgr_params(&sequence).is_some() {
+                kept.push(c);
+                kept.push_str(&sequence);
+            }
+        } else if !c.is_control() || c == '\n' || c == '\t' {
+            kept.push(c);
+        }
+    }
+
+    kept
+}
+
+/// The SGR sequences still in effect at the end of `text`: those after
+/// its last reset
+pub fn active_sgr(text: &str) -> String {
+    let mut active = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            continue;
+        }
+        let sequence = escape_sequence(&mut chars);
+        match sgr_params(&sequence) {
+            Some("" | "0") => active.clear(),
+            Some(_) => {
+                active.push(c);
+                active.push_str(&sequence);
+            }
+            None => {}
+        }
+    }
+
+    active
+}
+
+/// The parameters of an SGR sequence, given without its ESC
+fn sgr_params(sequence: &str) -> Option<&str> {
+    sequence
+        .strip_prefix('[')?
+        .strip_suffix('m')
+        .filter(|params| params.chars().all(|c| c.is_ascii_digit() || c == ';'))
+}
+
+/// `text` cut to `width` columns, ending in an ellipsis when anything was
+/// cut. Escape sequences are kept so colors still apply.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated = String::with_capacity(text.len());
+    let mut visible = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            truncated.push(c);
+            truncated.push_str(&escape_sequence(&mut chars));
+            continue;
+        }
+        if c.is_control() {
+            continue;
+        }
+
+        // Leave a column for the ellipsis
+        visible.push(c);
+        if visible.width() > width - 1 {
+            break;
+        }
+        truncated.push(c);
+    }
+
+    truncated.push('…');
+    if truncated.contains('\x1b') {
+        truncated.push_str("\x1b[0m");
+    }
+    truncated
+}
+
+/// The rest of an escape sequence whose ESC was just read
+fn escape_sequence(chars: &mut Peekable<Chars>) -> String {
+    let mut sequence = String::new();
+
+    match chars.next() {
+        // CSI: parameters up to a final byte in @..~
+        Some('[') => {
+            sequence.push('[');
+            for c in chars.by_ref() {
+                sequence.push(c);
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+        // OSC: up to BEL or ST (ESC \)
+        Some(']') => {
+            sequence.push(']');
+            while let Some(c) = chars.next() {
+                sequence.push(c);
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' && chars.peek() == Some(&'\\') {
+                    sequence.push('\\');
+                    chars.next();
+                    break;
+                }
+            }
+        }
+        // Two-character sequences
+        Some(c) => sequence.push(c),
+        None => {}
+    }
+
+    sequence
+}
+
+/// Spaces needed after `used` columns to reach `width`; never underflows
+pub fn padding(used: usize, width: usize) -> String {
+    " ".repeat(width.saturating_sub(used))
+}
+
+/// Tabs replaced with spaces up to the next tab stop
+pub fn expand_tabs(line: &str) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+
+    let mut expanded = String::with_capacity(line.len());
+    for c in line.chars() {
+        if c == '\t' {
+            let column = text_width(&expanded);
+            expanded.push_str(&" ".repeat(TAB_WIDTH - column % TAB_WIDTH));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+/// Side by side rows of two columns; the shorter one runs out first
+pub fn rows<L, R>(left: Vec<L>, right: Vec<R>) -> Vec<(Option<L>, Option<R>)> {
+    let height = left.len().max(right.len());
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    (0..height).map(|_| (left.next(), right.next())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width("arch"), 4);
+        assert_eq!(text_width("日本語"), 6);
+        assert_eq!(text_width("ｱｲｳ"), 3);
+        assert_eq!(text_width("e\u{301}"), 1);
+        assert_eq!(text_width("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(text_width("❤\u{fe0f}"), 2);
+        assert_eq!(text_width("🇫🇷"), 2);
+        assert_eq!(text_width(""), 0);
+    }
+
+    #[test]
+    fn test_escape_sequences_take_no_space() {
+        assert_eq!(text_width("\x1b[1;38;2;255;0;0mred\x1b[0m"), 3);
+        assert_eq!(
+            text_width("\x1b]8;;https://example.org\x1b\\link\x1b]8;;\x07"),
+            4
+        );
+        assert_eq!(text_width("a\rb\u{7f}c\x1b"), 3);
+        // A sequence cut short must not swallow anything it should not
+        assert_eq!(text_width("ab\x1b["), 2);
+        assert_eq!(
+            strip_escapes("\x1b[1m\x1b[96mOS\x1b[0m: Arch\n\x1b[40m   \x1b[0m\n"),
+            "OS: Arch\n   \n"
+        );
+
+        assert_eq!(
+            keep_sgr("\x1b[1;38;2;255;0;0mred\x1b[0m\x1b[m\tplain\n"),
+            "\x1b[1;38;2;255;0;0mred\x1b[0m\x1b[m\tplain\n"
+        );
+        assert_eq!(
+            keep_sgr(
+                "\x1b[2J\x1b[H\x1b[?25la\x1b]0;title\x07b\x1b]8;;x\x1b\\c\x1bcd\r\x08\x07e\x1b["
+            ),
+            "abcde"
+        );
+        assert_eq!(
+            active_sgr("\x1b[31mred\x1b[0m\x1b[1mbold\x1b[4m\x1b[2Kline"),
+            "\x1b[1m\x1b[4m"
+        );
+        assert_eq!(active_sgr("\x1b[31mred\x1b[m"), "");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Memory", 10), "Memory");
+        assert_eq!(truncate("Memory", 6), "Memory");
+        assert_eq!(truncate("Memory", 5), "Memo…");
+        assert_eq!(truncate("Memory", 1), "…");
+        assert_eq!(truncate("Memory", 0), "");
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(
+            truncate("\x1b[96mKey\x1b[0m: value", 6),
+            "\x1b[96mKey\x1b[0m: …\x1b[0m"
+        );
+        assert_eq!(text_width(&truncate("👨\u{200d}👩\u{200d}👧 family", 4)), 4);
+    }
+
+    #[test]
+    fn test_padding_and_tabs() {
+        assert_eq!(padding(3, 5), "  ");
+        assert_eq!(padding(7, 5), "");
+        assert_eq!(expand_tabs("a\tb"), "a       b");
+        assert_eq!(expand_tabs("日本\t|"), "日本    |");
+        assert_eq!(expand_tabs("no tabs"), "no tabs");
+    }
+
+    #[test]
+    fn test_rows() {
+        assert_eq!(
+            rows(vec!["a", "b", "c"], vec![1]),
+            vec![(Some("a"), Some(1)), (Some("b"), None), (Some("c"), None)]
+        );
+        assert_eq!(
+            rows(vec!["a"], vec![1, 2]),
+            vec![(Some("a"), Some(1)), (None, Some(2))]
+        );
+        assert!(rows(Vec::<&str>::new(), Vec::<u8>::new()).is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+use crate::colors;
+use crate::layout;
+use crate::style::Style;
+use crate::{Logo, LogoType};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether a problem stops the logo from loading or showing as drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a logo file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    /// 1-based line in the file, for problems on a single line
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{line}: {severity}: {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+/// Every problem in the logo file at `path`, or in each `.txt` file of
+/// the directory at `path`, sorted by file name
+pub fn lint_path(path: &Path) -> Result<Vec<(PathBuf, Vec<Problem>)>, String> {
+    let mut files = if path.is_dir() {
+        fs::read_dir(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|file| {
+            let bytes =
+                fs::read(&file).map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+            let name = file.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let problems = lint_logo(name, &bytes);
+            Ok((file, problems))
+        })
+        .collect()
+}
+
+/// Every problem in the contents of a logo file called `name`: the limits
+/// `Logo::from_file` enforces, invalid UTF-8, bad front matter, unknown or
+/// colorless placeholders, tabs, trailing whitespace and ragged widths
+pub fn lint_logo(name: &str, bytes: &[u8]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut error = |line: Option<usize>, message: String| {
+        problems.push(Problem {
+            severity: Severity::Error,
+            line,
+            message,
+        })
+    };
+
+    if bytes.len() as u64 > Logo::MAX_LOGO_SIZE {
+        error(
+            None,
+            format!(
+                "file is {} bytes (max: {} bytes)",
+                bytes.len(),
+                Logo::MAX_LOGO_SIZE
+            ),
+        );
+    }
+
+    let content = match std::str::from_utf8(bytes) {
+        Ok(content) => content.to_string(),
+        Err(e) => {
+            let line = bytes[..e.valid_up_to()]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                + 1;
+            error(Some(line), "invalid UTF-8".to_string());
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+
+    let (front_matter, art) = match colors::parse_front_matter(&content) {
+        Ok((front_matter, art)) => (front_matter.unwrap_or_default(), art),
+        Err(e) => {
+            error(None, e);
+            (colors::LogoEntry::default(), content.as_str())
+        }
+    };
+    // Lines of the front matter come before the art
+    let first_line = content[..content.len() - art.len()].lines().count() + 1;
+
+    let colors = if front_matter.colors.is_empty() {
+        if colors::find_logo(name).is_none() {
+            problems.push(Problem {
+                severity: Severity::Warning,
+                line: None,
+                message: format!(
+                    "no colors for '{name}' in the logo table or front matter, white and grey are used"
+                ),
+            });
+        }
+        colors::get_logo_colors(name)
+    } else {
+        colors::colors_by_placeholder(&front_matter.colors)
+    };
+
+    let lines: Vec<&str> = art.lines().collect();
+    if lines.len() > Logo::MAX_LINES {
+        problems.push(Problem {
+            severity: Severity::Error,
+            line: None,
+            message: format!("{} lines (max: {})", lines.len(), Logo::MAX_LINES),
+        });
+    }
+
+    let mut widths = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let number = first_line + i;
+        let mut report = |severity: Severity, message: String| {
+            problems.push(Problem {
+                severity,
+                line: Some(number),
+                message,
+            })
+        };
+
+        if line.contains('\x1b') {
+            report(Severity::Error, "ANSI escape sequence".to_string());
+        }
+        for message in placeholder_problems(line, &colors) {
+            report(Severity::Error, message);
+        }
+        if line.contains('\t') {
+            report(
+                Severity::Warning,
+                "tab, expanded to 8-column stops".to_string(),
+            );
+        }
+        if line.trim_end() != *line {
+            report(Severity::Warning, "trailing whitespace".to_string());
+        }
+
+        let width = Logo::calculate_display_width(&layout::expand_tabs(line), LogoType::Text);
+        if width > Logo::MAX_LINE_WIDTH {
+            report(
+                Severity::Error,
+                format!("{width} columns wide (max: {})", Logo::MAX_LINE_WIDTH),
+            );
+        }
+        widths.push(width);
+    }
+
+    if let (Some(narrowest), Some(widest)) = (widths.iter().min(), widths.iter().max())
+        && narrowest != widest
+    {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            line: None,
+            message: format!("ragged widths: lines are {narrowest} to {widest} columns wide"),
+        });
+    }
+
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+/// Placeholders in `line` that are malformed or name a color the logo
+/// does not have
+fn placeholder_problems(
+    line: &str,
+    colors: &std::collections::HashMap<String, crossterm::style::Color>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut missing = |number: &str| {
+        if !colors.contains_key(&format!("${number}")) {
+            problems.push(format!("`${number}` has no color"));
+        }
+    };
+    let mut unknown = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('$', Some(next)) if next.is_ascii_digit() => {
+                missing(&chars.next().unwrap().to_string());
+            }
+            ('$', Some('{')) => {
+                chars.next();
+                let mut spec = String::new();
+                let closed = chars.by_ref().any(|ch| {
+                    if ch != '}' {
+                        spec.push(ch);
+                    }
+                    ch == '}'
+                });
+                if !closed {
+                    unknown.push(format!("unterminated placeholder `${{{spec}`"));
+                    continue;
+                }
+
+                let (color, rest) = spec.split_once(';').unwrap_or((&spec, ""));
+                let style = match color.strip_prefix('c') {
+                    Some(number) if number.len() == 1 && number.as_bytes()[0].is_ascii_digit() => {
+                        missing(number);
+                        rest
+                    }
+                    _ => spec.as_str(),
+                };
+                if Style::parse(style).is_err() {
+                    unknown.push(format!("unknown placeholder `${{{spec}}}`"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    problems.extend(unknown);
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(name: &str, content: &str) -> Vec<String> {
+        lint_logo(name, content.as_bytes())
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_logo() {
+        assert_eq!(messages("arch", "$1/\\\n$2\\/\n"), Vec::<String>::new());
+
+        assert_eq!(
+            messages(
+                "arch",
+                "$1ok\n$5x${nonsense}\n${c2;italic}y \n\tz\n\x1b[31mw\n${c1"
+            ),
+            vec![
+                "warning: ragged widths: lines are 0 to 9 columns wide",
+                "2: error: `$5` has no color",
+                "2: error: unknown placeholder `${nonsense}`",
+                "3: warning: trailing whitespace",
+                "4: warning: tab, expanded to 8-column stops",
+                "5: error: ANSI escape sequence",
+                "6: error: unterminated placeholder `${c1`",
+            ]
+        );
+
+        // Front matter defines the colors and shifts line numbers
+        assert_eq!(
+            messages("custom", "---\ncolors = red\n---\n$1a\n$2b\n"),
+            vec!["5: error: `$2` has no color"]
+        );
+        assert_eq!(
+            messages("custom", "$1a\n"),
+            vec![
+                "warning: no colors for 'custom' in the logo table or front matter, white and grey are used"
+            ]
+        );
+        assert_eq!(
+            messages("arch", "---\nsparkles = yes\n---\n$1a\n")[0],
+            "error: front matter line 2: unknown setting 'sparkles'"
+        );
+
+        let problems = lint_logo("arch", b"$1ok\n\xff\xfe\n");
+        assert_eq!(problems[0].to_string(), "2: error: invalid UTF-8");
+    }
+
+    #[test]
+    fn test_bundled_logos() {
+        let linted = lint_path(Path::new("src/logo/ascii")).unwrap();
+        assert!(linted.len() > 400);
+
+        for (path, problems) in linted {
+            for problem in problems {
+                assert!(
+                    problem.severity == Severity::Warning
+                        && !problem.message.starts_with("no colors"),
+                    "{}: {problem}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -1,24 +1,52 @@
+mod battery;
+mod catalog;
 mod colors;
-
+mod disk;
+mod dmi;
+mod exec;
+mod layout;
+mod lint;
+mod memory;
+mod palette;
+mod probe;
+mod shell;
+mod smbios;
+mod style;
+mod swap;
+mod sysroot;
+mod terminal;
+mod theme;
+mod upstream;
+
+use battery::Battery;
+use catalog::{LogoFile, LogoSource};
 use clap::Parser;
 use crossterm::style::Color;
-use glob::glob;
-use nix::sys::statfs::statfs;
-use nix::sys::{sysinfo, utsname::uname};
+use disk::{Disk, DiskFilter, DiskPattern};
+use dmi::Dmi;
+use memory::{Dimm, MemoryDetail};
 use regex::Regex;
-use std::collections::{HashMap, HashSet};
-use std::env;
+use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::LazyLock;
+use std::time::{Duration, SystemTime};
+use style::Style;
+use swap::{SwapDevice, ZswapStatus};
+use sysroot::SysRoot;
+use terminal::{ColorChoice, ColorDepth, Terminal};
+use theme::{LogoPosition, PercentStyle, Theme};
+
+/// Narrowest information column worth keeping the logo beside
+const MIN_INFO_WIDTH: usize = 40;
+
+/// How long CPU usage is measured over
+const CPU_USAGE_INTERVAL: Duration = Duration::from_millis(200);
 
 static IP_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"inet\s+(\d+\.\d+\.\d+\.\d+/\d+)").unwrap());
 
-static VERSION_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(\d+\.\d+(?:\.\d+)?)").unwrap());
-
 #[derive(Parser, Debug)]
 #[command(
     name = "fastfetch-rs",
@@ -27,15 +55,100 @@ static VERSION_REGEX: LazyLock<Regex> =
     long_about = None
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Logo to show: a name, `random`, or `daily` for one that changes
+    /// every day; both pick from a list when given one, e.g. `random:arch,gentoo`
     #[arg(short, long)]
     logo: Option<String>,
 
-    #[arg(long = "logo-file")]
+    /// Logo file to show, or `-` to read the logo from standard input
+    #[arg(long = "logo-file", value_name = "FILE")]
     logo_file: Option<PathBuf>,
 
+    /// Logo to show, given as text with `\n` between lines, or as a
+    /// `data:` URI
+    #[arg(
+        long = "logo-inline",
+        value_name = "TEXT",
+        conflicts_with = "logo_file"
+    )]
+    logo_inline: Option<String>,
+
+    /// What `--logo-file` and `--logo-inline` hold: text with color
+    /// placeholders, or art already colored with escape sequences
+    #[arg(long = "logo-type", value_enum, value_name = "TYPE", default_value_t = LogoType::Text)]
+    logo_type: LogoType,
+
     #[arg(long)]
     list_logos: bool,
 
+    /// Where to put the logo: left, right, top or none. Narrow terminals get
+    /// the small logo, or the logo on top, instead.
+    #[arg(long = "logo-position", value_name = "POSITION")]
+    logo_position: Option<String>,
+
+    /// Columns between the logo and the information
+    #[arg(long = "logo-padding", value_name = "COLUMNS")]
+    logo_padding: Option<usize>,
+
+    /// Layout and colors of the information column, from a bundled preset
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// List the bundled presets
+    #[arg(long)]
+    list_presets: bool,
+
+    /// Style of the keys, e.g. `italic;fg=#ff8800` (default: the logo's color, bold)
+    #[arg(long = "key-color", value_name = "STYLE")]
+    key_color: Option<String>,
+
+    /// Style of the values (default: the terminal's)
+    #[arg(long = "value-color", value_name = "STYLE")]
+    value_color: Option<String>,
+
+    /// Text between a key and its value
+    #[arg(long, value_name = "TEXT")]
+    separator: Option<String>,
+
+    /// Pad keys to this many columns so values line up
+    #[arg(long = "key-width", value_name = "COLUMNS")]
+    key_width: Option<usize>,
+
+    /// Title text, with `{user}` and `{host}` substituted
+    #[arg(long = "title-format", value_name = "FORMAT")]
+    title_format: Option<String>,
+
+    /// Character the title is underlined with
+    #[arg(long = "underline-char", value_name = "CHAR")]
+    underline_char: Option<String>,
+
+    /// Show usage as a number, a bar, or both: percent, bar, both
+    #[arg(long = "percent-style", value_name = "STYLE")]
+    percent_style: Option<String>,
+
+    /// Usage levels where the color turns medium and high, e.g. `50,80`
+    #[arg(long = "percent-thresholds", value_name = "LOW,HIGH")]
+    percent_thresholds: Option<String>,
+
+    /// Colors for low, medium and high usage, e.g. `green,yellow,red`, or `none`
+    #[arg(long = "percent-colors", value_name = "COLORS")]
+    percent_colors: Option<String>,
+
+    /// Number of cells in usage bars
+    #[arg(long = "bar-width", value_name = "CELLS")]
+    bar_width: Option<usize>,
+
+    /// Characters for the filled and empty parts of usage bars, e.g. `#-`
+    #[arg(long = "bar-chars", value_name = "CHARS")]
+    bar_chars: Option<String>,
+
+    /// Characters around usage bars, e.g. `[]`, or `none`
+    #[arg(long = "bar-border", value_name = "CHARS")]
+    bar_border: Option<String>,
+
     // Logo color overrides
     #[arg(long = "logo-color-1")]
     logo_color_1: Option<String>,
@@ -63,11 +176,134 @@ struct Args {
 
     #[arg(long = "logo-color-9")]
     logo_color_9: Option<String>,
+
+    /// Only show disks matching PATTERN: a mount point glob, `fs:<type>` or `dev:<device>`
+    #[arg(long = "disk-include", value_name = "PATTERN")]
+    disk_include: Vec<String>,
+
+    /// Hide disks matching PATTERN: a mount point glob, `fs:<type>` or `dev:<device>`
+    #[arg(long = "disk-exclude", value_name = "PATTERN")]
+    disk_exclude: Vec<String>,
+
+    /// Also show inode usage for each disk
+    #[arg(long = "disk-inodes")]
+    disk_inodes: bool,
+
+    /// Unit system for sizes: binary (KiB, MiB, ...) or decimal (kB, MB, ...)
+    #[arg(long = "size-units", value_enum, default_value_t = SizeUnits::Iec)]
+    size_units: SizeUnits,
+
+    /// Extra memory fields to show: buffers, cache, shared, zram, zswap, hugepages
+    #[arg(long = "memory-details", value_enum, value_delimiter = ',')]
+    memory_details: Vec<MemoryDetail>,
+
+    /// Never run external programs; modules that need one are skipped
+    #[arg(long = "no-exec")]
+    no_exec: bool,
+
+    /// When to use colors: auto (on a terminal, honoring NO_COLOR,
+    /// CLICOLOR_FORCE and TERM=dumb), always or never
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorChoice::Auto,
+        global = true
+    )]
+    color: ColorChoice,
+
+    /// Colors the terminal can show: 16, 256 or truecolor (default: from
+    /// COLORTERM, TERM and terminfo). Other colors are mapped to the nearest.
+    #[arg(long = "color-depth", value_enum, value_name = "DEPTH", global = true)]
+    color_depth: Option<ColorDepth>,
+
+    /// Read the system from a snapshot directory instead of `/` (for testing)
+    #[arg(long, value_name = "DIR", conflicts_with = "from_probe")]
+    sysroot: Option<PathBuf>,
+
+    /// Record every file, command and environment variable the probes read
+    /// into a tar archive, with hostname and addresses redacted
+    #[arg(long = "dump-probe", value_name = "FILE")]
+    dump_probe: Option<PathBuf>,
+
+    /// Replay a snapshot written by --dump-probe instead of reading the system
+    #[arg(long = "from-probe", value_name = "FILE")]
+    from_probe: Option<PathBuf>,
+
+    /// Print the logo table for upstream fastfetch's src/logo/builtin.c
+    #[arg(long = "import-logos", value_name = "FILE", hide = true)]
+    import_logos: Option<PathBuf>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// List, preview, search and check logos
+    #[command(alias = "logo")]
+    Logos {
+        #[command(subcommand)]
+        command: LogosCommand,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum LogosCommand {
+    /// List the logos with their aliases and variants
+    List(LogoSelection),
+    /// Draw a logo in its colors, without the information
+    Show {
+        name: String,
+        /// Draw its small variant instead
+        #[arg(long)]
+        small: bool,
+    },
+    /// Draw the logos side by side, a screenful at a time
+    Gallery(LogoSelection),
+    /// Find logos whose name, aliases or variants contain TERM
+    Search { term: String },
+    /// Report every problem in a logo file, or in each .txt file of a directory
+    Lint { path: PathBuf },
+}
+
+#[derive(clap::Args, Debug)]
+struct LogoSelection {
+    /// Only logos whose name matches this glob, e.g. `arch*`
+    pattern: Option<String>,
+    /// Include variants (`_small`, `_old`, `2`, ...) as logos of their own
+    #[arg(long)]
+    variants: bool,
+}
+
+/// What a logo file holds
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+enum LogoType {
+    /// Text with `$1`-style color placeholders
+    #[default]
+    Text,
+    /// Art colored with ANSI escape sequences, as made by jp2a or chafa
+    Ansi,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+enum SizeUnits {
+    #[default]
+    Iec,
+    Si,
+}
+
+/// Options that change what gets detected and how sizes are reported
+#[derive(Default)]
+struct DetectOptions {
+    disk_filter: DiskFilter,
+    disk_inodes: bool,
+    size_units: SizeUnits,
+    memory_details: Vec<MemoryDetail>,
+    /// Measure CPU usage over this long; `None` skips it
+    cpu_usage_interval: Option<Duration>,
 }
 
 impl Args {
-    /// Get color overrides as a HashMap
-    fn get_color_overrides(&self) -> HashMap<String, Color> {
+    /// Get color overrides as a HashMap; a color alone keeps the logo bold
+    fn get_color_overrides(&self) -> HashMap<String, Style> {
         let mut overrides = HashMap::new();
 
         let color_options = [
@@ -84,16 +320,86 @@ impl Args {
 
         for (color_opt, key) in color_options {
             if let Some(color_str) = color_opt {
-                if let Some(color) = parse_color(color_str) {
-                    overrides.insert(key.to_string(), color);
-                } else {
-                    eprintln!("Warning: Invalid color '{color_str}' for {key}");
+                match Style::parse(color_str) {
+                    Ok(style) => {
+                        overrides.insert(key.to_string(), style.over(Style::KEY));
+                    }
+                    Err(e) => eprintln!("Warning: Invalid color '{color_str}' for {key}: {e}"),
                 }
             }
         }
 
         overrides
     }
+
+    /// Build the disk filter from `--disk-include` / `--disk-exclude`
+    fn get_disk_filter(&self) -> DiskFilter {
+        let parse = |specs: &[String]| {
+            specs
+                .iter()
+                .filter_map(|spec| {
+                    let pattern = DiskPattern::parse(spec);
+                    if pattern.is_none() {
+                        eprintln!("Warning: Invalid disk pattern '{spec}'");
+                    }
+                    pattern
+                })
+                .collect()
+        };
+
+        DiskFilter {
+            include: parse(&self.disk_include),
+            exclude: parse(&self.disk_exclude),
+        }
+    }
+
+    /// The preset (or default theme) with the styling options applied on top
+    fn get_theme(&self) -> Result<Theme, String> {
+        let mut theme = match &self.preset {
+            Some(name) => Theme::preset(name)?,
+            None => Theme::default(),
+        };
+
+        let key_width = self.key_width.map(|width| width.to_string());
+        let bar_width = self.bar_width.map(|width| width.to_string());
+        let logo_padding = self.logo_padding.map(|padding| padding.to_string());
+        let options = [
+            ("logo-position", &self.logo_position),
+            ("logo-padding", &logo_padding),
+            ("key-color", &self.key_color),
+            ("value-color", &self.value_color),
+            ("separator", &self.separator),
+            ("key-width", &key_width),
+            ("title-format", &self.title_format),
+            ("underline-char", &self.underline_char),
+            ("percent-style", &self.percent_style),
+            ("percent-thresholds", &self.percent_thresholds),
+            ("percent-colors", &self.percent_colors),
+            ("bar-width", &bar_width),
+            ("bar-chars", &self.bar_chars),
+            ("bar-border", &self.bar_border),
+        ];
+        for (key, value) in options {
+            if let Some(value) = value {
+                theme
+                    .set(key, value)
+                    .map_err(|e| format!("Invalid --{key}: {e}"))?;
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Detection options, measuring CPU usage only when the theme shows it
+    fn get_detect_options(&self, theme: &Theme) -> DetectOptions {
+        DetectOptions {
+            disk_filter: self.get_disk_filter(),
+            disk_inodes: self.disk_inodes,
+            size_units: self.size_units,
+            memory_details: self.memory_details.clone(),
+            cpu_usage_interval: theme.shows("cpuusage").then_some(CPU_USAGE_INTERVAL),
+        }
+    }
 }
 
 /// Parse a color string into a crossterm Color
@@ -142,6 +448,9 @@ fn parse_color(color_str: &str) -> Option<Color> {
         "gold" => Some(Color::AnsiValue(220)),
         "silver" => Some(Color::AnsiValue(250)),
 
+        // The terminal's own foreground
+        "default" => Some(Color::Reset),
+
         _ => {
             if let Some(hex) = color_str.strip_prefix('#')
                 && hex.len() == 6
@@ -164,27 +473,71 @@ fn parse_color(color_str: &str) -> Option<Color> {
 struct SystemInfo {
     username: String,
     hostname: String,
-    os: String,
-    host: String,
-    kernel: String,
-    uptime: String,
-    packages: String,
-    shell: String,
-    display: String,
-    terminal: String,
-    cpu: String,
-    gpu: String,
-    memory: String,
+    os: Option<String>,
+    host: Option<String>,
+    bios: Option<String>,
+    board: Option<String>,
+    chassis: Option<String>,
+    kernel: Option<String>,
+    uptime: Option<String>,
+    packages: Option<String>,
+    shell: Option<String>,
+    display: Option<String>,
+    terminal: Option<String>,
+    cpu: Option<String>,
+    cpu_usage: Option<u32>,
+    gpu: Option<String>,
+    memory: Option<String>,
     memory_percent: u32,
-    swap: String,
-    disks: Vec<(String, String, u32)>, // (mount_point, info, percent)
-    local_ip: String,
-    locale: String,
+    memory_breakdown: String,
+    physical_memory: Vec<String>,
+    swaps: Option<Vec<SwapDevice>>,
+    zswap: Option<String>,
+    disks: Vec<Disk>,
+    disk_inodes: bool,
+    size_units: SizeUnits,
+    /// Address and interface
+    local_ip: Option<(String, String)>,
+    batteries: Vec<Battery>,
+    locale: Option<String>,
+}
+
+/// One row of the information column, before colors are applied
+enum InfoLine {
+    /// Already colored (the title and its underline)
+    Text(String),
+    Field {
+        key: String,
+        /// The whole value, or the part before the percentage
+        value: String,
+        /// Percentage shown as `(N%)` after `value`, or as `N%` when it is empty
+        percent: Option<Percent>,
+        /// The part after the percentage
+        rest: String,
+    },
+}
+
+/// A percentage in an info line, colored by how close it is to trouble
+#[derive(Clone, Copy)]
+enum Percent {
+    /// Memory, disk or CPU usage: high is bad
+    Usage(u32),
+    /// Battery charge: low is bad
+    Charge(u32),
 }
 
+#[derive(Default)]
 struct Logo {
     lines: Vec<String>,
-    colors: HashMap<String, Color>,
+    colors: HashMap<String, Style>,
+    /// Color for keys, instead of the second logo color
+    key_color: Option<Color>,
+    /// Color for the title, instead of the key color
+    title_color: Option<Color>,
+    /// Variant for narrow terminals
+    small: Option<LogoFile>,
+    /// Pre-colored art starts in the terminal's colors instead of color 1
+    logo_type: LogoType,
 }
 
 impl Logo {
@@ -192,24 +545,70 @@ impl Logo {
     const MAX_LINES: usize = 100;
     const MAX_LINE_WIDTH: usize = 200;
 
-    fn from_file(path: &Path, color_overrides: HashMap<String, Color>) -> Result<Self, String> {
-        let metadata =
-            fs::metadata(path).map_err(|e| format!("Failed to read logo file metadata: {e}"))?;
+    fn from_file(
+        path: &Path,
+        color_overrides: HashMap<String, Style>,
+        logo_type: LogoType,
+    ) -> Result<Self, String> {
+        let bytes = fs::File::open(path)
+            .and_then(Self::read_capped)
+            .map_err(|e| format!("Failed to read logo file: {e}"))?
+            .ok_or_else(|| format!("Logo file too large (max: {} bytes)", Self::MAX_LOGO_SIZE))?;
+        let content =
+            String::from_utf8(bytes).map_err(|_| "Logo file is not valid UTF-8".to_string())?;
+
+        let filename = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+
+        Self::from_content(filename, &content, Some(path), color_overrides, logo_type)
+    }
+
+    /// All of `reader`, or `None` when it holds more than `MAX_LOGO_SIZE`
+    /// bytes. Pipes and devices have no size to check up front, so the
+    /// limit applies while reading.
+    fn read_capped(reader: impl Read) -> io::Result<Option<Vec<u8>>> {
+        let mut bytes = Vec::new();
+        reader
+            .take(Self::MAX_LOGO_SIZE + 1)
+            .read_to_end(&mut bytes)?;
+        Ok((bytes.len() as u64 <= Self::MAX_LOGO_SIZE).then_some(bytes))
+    }
 
-        if metadata.len() > Self::MAX_LOGO_SIZE {
+    /// A logo called `name` from the contents of its file, found at `path`
+    /// unless it is bundled or was given on the command line
+    fn from_content(
+        name: &str,
+        content: &str,
+        path: Option<&Path>,
+        color_overrides: HashMap<String, Style>,
+        logo_type: LogoType,
+    ) -> Result<Self, String> {
+        if content.len() as u64 > Self::MAX_LOGO_SIZE {
             return Err(format!(
                 "Logo file too large: {} bytes (max: {} bytes)",
-                metadata.len(),
+                content.len(),
                 Self::MAX_LOGO_SIZE
             ));
         }
 
-        let content =
-            fs::read_to_string(path).map_err(|e| format!("Failed to read logo file: {e}"))?;
+        let sanitized;
+        let content = match logo_type {
+            LogoType::Text if content.contains('\x1b') => {
+                return Err("Logo file contains ANSI escape sequences".to_string());
+            }
+            LogoType::Text => content,
+            // Only colors are kept: the art must not move the cursor, clear
+            // the screen or talk to the terminal
+            LogoType::Ansi => {
+                sanitized = layout::keep_sgr(content);
+                &sanitized
+            }
+        };
 
-        if content.contains('\x1b') || content.contains('\u{001b}') {
-            return Err("Logo file contains ANSI escape sequences".to_string());
-        }
+        let (front_matter, content) = colors::parse_front_matter(content)?;
+        let front_matter = front_matter.unwrap_or_default();
 
         let line_count = content.lines().count();
         if line_count > Self::MAX_LINES {
@@ -220,12 +619,16 @@ impl Logo {
             ));
         }
 
-        let filename = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
-
-        let mut colors = colors::get_logo_colors(filename);
+        // The file's own front matter comes first, then the logo table
+        let entry = colors::find_logo(name);
+        let mut colors: HashMap<String, Style> = if front_matter.colors.is_empty() {
+            colors::get_logo_colors(name)
+        } else {
+            colors::colors_by_placeholder(&front_matter.colors)
+        }
+        .into_iter()
+        .map(|(key, color)| (key, Style::logo(color)))
+        .collect();
 
         if colors.is_empty() {
             colors = Self::get_default_colors();
@@ -235,13 +638,21 @@ impl Logo {
             colors.insert(key, color);
         }
 
-        let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+        let mut lines: Vec<String> = content.lines().map(layout::expand_tabs).collect();
+        if logo_type == LogoType::Ansi {
+            // Lines are drawn apart, so each restates the colors it continues in
+            let mut active = String::new();
+            for line in &mut lines {
+                line.insert_str(0, &active);
+                active = layout::active_sgr(line);
+            }
+        }
 
         for (i, line) in lines.iter().enumerate() {
-            let display_width = Logo::calculate_display_width(line);
+            let display_width = Logo::calculate_display_width(line, logo_type);
             if display_width > Self::MAX_LINE_WIDTH {
                 return Err(format!(
-                    "Line {} is too wide: {} chars (max: {})",
+                    "Line {} is too wide: {} columns (max: {})",
                     i + 1,
                     display_width,
                     Self::MAX_LINE_WIDTH
@@ -249,258 +660,238 @@ impl Logo {
             }
         }
 
-        Ok(Logo { lines, colors })
-    }
-
-    fn get_default_colors() -> HashMap<String, Color> {
-        let mut colors = HashMap::new();
-        colors.insert("$1".to_string(), Color::Cyan);
-        colors.insert("$2".to_string(), Color::Blue);
-        colors.insert("$3".to_string(), Color::Green);
-        colors.insert("$4".to_string(), Color::Yellow);
-        colors.insert("$5".to_string(), Color::Red);
-        colors.insert("$6".to_string(), Color::Magenta);
-        colors.insert("$7".to_string(), Color::White);
-        colors.insert("$8".to_string(), Color::DarkGrey);
-        colors
+        Ok(Logo {
+            lines,
+            colors,
+            logo_type,
+            key_color: front_matter
+                .key_color
+                .or(entry.and_then(|entry| entry.key_color)),
+            title_color: front_matter
+                .title_color
+                .or(entry.and_then(|entry| entry.title_color)),
+            small: Self::small_variant(
+                name,
+                path,
+                front_matter
+                    .small
+                    .as_deref()
+                    .or(entry.and_then(|entry| entry.small.as_deref())),
+            ),
+        })
     }
 
-    fn list_available() -> Vec<String> {
-        let mut logos = Vec::new();
-
-        let mut patterns = vec![
-            "src/logo/ascii/*.txt".to_string(),
-            "logo/ascii/*.txt".to_string(),
-            "/usr/share/fastfetch-rs/logos/*.txt".to_string(),
-            "/usr/local/share/fastfetch-rs/logos/*.txt".to_string(),
-        ];
-
-        if let Ok(exe_path) = std::env::current_exe()
-            && let Some(prefix) = exe_path.parent().and_then(|p| p.parent())
-        {
-            patterns.push(format!(
-                "{}/share/fastfetch-rs/logos/*.txt",
-                prefix.display()
-            ));
-        }
-
-        for pattern in patterns {
-            if let Ok(paths) = glob(&pattern) {
-                for path in paths.flatten() {
-                    if let Some(stem) = path.file_stem()
-                        && let Some(name) = stem.to_str()
-                        && !name.ends_with("_small")
-                        && !name.ends_with("_old")
-                    {
-                        logos.push(name.to_string());
-                    }
-                }
-            }
-        }
+    /// The `small` variant of logo `name`, by default `<name>_small`: the
+    /// file next to `path`, or else any logo of that name
+    fn small_variant(name: &str, path: Option<&Path>, small: Option<&str>) -> Option<LogoFile> {
+        let small = match small {
+            Some(small) => small.to_string(),
+            None if name.ends_with("_small") => return None,
+            None => format!("{name}_small"),
+        };
 
-        logos.sort();
-        logos.dedup();
-        logos
+        path.map(|path| path.with_file_name(format!("{small}.txt")))
+            .filter(|path| path.is_file())
+            .map(|path| LogoFile {
+                name: small.clone(),
+                source: LogoSource::File(path),
+            })
+            .or_else(|| catalog::find_file(&small))
     }
 
-    fn load(name: &str, color_overrides: HashMap<String, Color>) -> Result<Self, String> {
-        let mut possible_paths = vec![
-            format!("src/logo/ascii/{name}.txt"),
-            format!("logo/ascii/{name}.txt"),
-        ];
-
-        if let Ok(logo_dir) = std::env::var("FASTFETCH_LOGO_DIR") {
-            possible_paths.push(format!("{logo_dir}/{name}.txt"));
-        }
-
-        if let Ok(data_home) = std::env::var("XDG_DATA_HOME") {
-            possible_paths.push(format!("{data_home}/fastfetch-rs/logos/{name}.txt"));
-        }
+    fn get_default_colors() -> HashMap<String, Style> {
+        [
+            Color::Cyan,
+            Color::Blue,
+            Color::Green,
+            Color::Yellow,
+            Color::Red,
+            Color::Magenta,
+            Color::White,
+            Color::DarkGrey,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, color)| (format!("${}", i + 1), Style::logo(color)))
+        .collect()
+    }
 
-        if let Ok(exe_path) = std::env::current_exe()
-            && let Some(prefix) = exe_path.parent().and_then(|p| p.parent())
-        {
-            let prefix_display = prefix.display();
-            possible_paths.push(format!(
-                "{prefix_display}/share/fastfetch-rs/logos/{name}.txt"
-            ));
-        }
+    /// Names of the available logos, without their variants
+    fn list_available() -> Vec<String> {
+        catalog::listings(catalog::available(), false)
+            .into_iter()
+            .map(|listing| listing.logo.name)
+            .collect()
+    }
 
-        possible_paths.extend(vec![
-            format!("/usr/share/fastfetch-rs/logos/{name}.txt"),
-            format!("/usr/local/share/fastfetch-rs/logos/{name}.txt"),
-        ]);
+    fn load(name: &str, color_overrides: HashMap<String, Style>) -> Result<Self, String> {
+        catalog::find(name)
+            .ok_or_else(|| format!("Logo '{name}' not found"))?
+            .load(color_overrides)
+    }
 
-        if let Ok(home) = std::env::var("HOME") {
-            possible_paths.push(format!("{home}/.local/share/fastfetch-rs/logos/{name}.txt"));
+    /// A logo line with its placeholders turned into escape sequences.
+    /// `$N` and `${cN}` switch to logo color N; `${...}` may also hold a
+    /// style spec, e.g. `${c2;italic}` or `${bold;fg=#ff8800}`, layered
+    /// over color N or the current style. Pre-colored art has no
+    /// placeholders and is kept as it is.
+    fn render_line(&self, line: &str, current_style: &mut Option<Style>) -> String {
+        if self.logo_type == LogoType::Ansi {
+            return line.to_string();
         }
+        let mut rendered = String::new();
+        let mut chars = line.chars().peekable();
 
-        for path_str in &possible_paths {
-            let path = Path::new(path_str);
-            if path.exists() {
-                return Logo::from_file(path, color_overrides);
-            }
+        if let Some(style) = current_style {
+            rendered.push_str(&style.sgr());
         }
 
-        let test_colors = colors::get_logo_colors(name);
-
-        if test_colors.len() > 2
-            || test_colors.get("$1") != Some(&Color::White)
-            || test_colors.get("$2") != Some(&Color::Grey)
-        {
-            for available_logo in Self::list_available() {
-                if colors::get_logo_colors(&available_logo) == test_colors {
-                    for path_str in &possible_paths {
-                        let new_path = path_str.replace(name, &available_logo);
-                        let path = Path::new(&new_path);
-                        if path.exists() {
-                            return Logo::from_file(path, color_overrides);
-                        }
-                    }
+        while let Some(ch) = chars.next() {
+            let style = match (ch, chars.peek()) {
+                ('$', Some(next)) if next.is_numeric() => {
+                    let color_key = format!("${}", chars.next().unwrap());
+                    self.colors.get(&color_key).copied()
+                }
+                ('$', Some('{')) => {
+                    chars.next();
+                    let spec: String = chars.by_ref().take_while(|&ch| ch != '}').collect();
+                    self.placeholder_style(&spec, *current_style)
+                }
+                _ => {
+                    rendered.push(ch);
+                    continue;
                 }
+            };
+
+            if let Some(style) = style.filter(|&style| Some(style) != *current_style) {
+                rendered.push_str(&style.switch_from(&current_style.unwrap_or_default()));
+                *current_style = Some(style);
             }
         }
 
-        Err(format!("Logo '{name}' not found"))
+        rendered
     }
 
-    fn render_line(&self, line: &str, current_color: &mut Option<Color>) -> String {
-        let mut rendered = String::new();
-        let mut chars = line.chars().peekable();
-
-        if let Some(color) = current_color {
-            rendered.push_str(&self.color_to_ansi(color));
-        }
+    /// Every line, colored and padded to the width of the widest
+    fn rendered_lines(&self) -> Vec<String> {
+        let width = self.width();
+        let mut current_style = match self.logo_type {
+            LogoType::Text => self.colors.get("$1").copied(),
+            LogoType::Ansi => None,
+        };
 
-        while let Some(ch) = chars.next() {
-            if ch == '$' {
-                if let Some(&next_ch) = chars.peek() {
-                    if next_ch.is_numeric() {
-                        chars.next();
-                        let color_key = format!("${next_ch}");
-                        if let Some(color) = self.colors.get(&color_key) {
-                            rendered.push_str(&self.color_to_ansi(color));
-                            *current_color = Some(*color);
-                        }
-                    } else if next_ch == '{' {
-                        chars.next();
-                        let mut color_id = String::new();
-
-                        while let Some(&ch) = chars.peek() {
-                            if ch == '}' {
-                                chars.next();
-                                break;
-                            }
-                            color_id.push(chars.next().unwrap());
-                        }
+        self.lines
+            .iter()
+            .map(|line| {
+                let line_width = Logo::calculate_display_width(line, self.logo_type);
+                let line = self.render_line(line, &mut current_style);
+                format!("{line}\x1b[0m{}", layout::padding(line_width, width))
+            })
+            .collect()
+    }
 
-                        if color_id.starts_with('c')
-                            && color_id.len() == 2
-                            && let Some(num) = color_id.chars().nth(1)
-                            && num.is_numeric()
-                        {
-                            let color_key = format!("${num}");
-                            if let Some(color) = self.colors.get(&color_key) {
-                                rendered.push_str(&self.color_to_ansi(color));
-                                *current_color = Some(*color);
-                            }
-                        }
-                    } else {
-                        rendered.push('$');
-                    }
-                } else {
-                    rendered.push('$');
-                }
-            } else {
-                rendered.push(ch);
+    /// Style selected by a `${...}` placeholder; `None` when it is invalid
+    fn placeholder_style(&self, spec: &str, current: Option<Style>) -> Option<Style> {
+        let (base, spec) = match spec.split_once(';').unwrap_or((spec, "")) {
+            (color, rest)
+                if color.len() == 2
+                    && color.starts_with('c')
+                    && color[1..].chars().all(|c| c.is_ascii_digit()) =>
+            {
+                (
+                    self.colors.get(&format!("${}", &color[1..])).copied()?,
+                    rest,
+                )
             }
-        }
+            _ => (current.unwrap_or_default(), spec),
+        };
+        Some(Style::parse(spec).ok()?.over(base))
+    }
 
-        rendered
+    /// Columns taken by the widest line
+    fn width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| Self::calculate_display_width(line, self.logo_type))
+            .max()
+            .unwrap_or(0)
     }
 
-    fn color_to_ansi(&self, color: &Color) -> String {
-        match color {
-            Color::Black => "\x1b[30m".to_string(),
-            Color::DarkRed => "\x1b[31m".to_string(),
-            Color::DarkGreen => "\x1b[32m".to_string(),
-            Color::DarkYellow => "\x1b[33m".to_string(),
-            Color::DarkBlue => "\x1b[34m".to_string(),
-            Color::DarkMagenta => "\x1b[35m".to_string(),
-            Color::DarkCyan => "\x1b[36m".to_string(),
-            Color::Grey => "\x1b[37m".to_string(),
-            Color::DarkGrey => "\x1b[90m".to_string(),
-            Color::Red => "\x1b[91m".to_string(),
-            Color::Green => "\x1b[92m".to_string(),
-            Color::Yellow => "\x1b[93m".to_string(),
-            Color::Blue => "\x1b[94m".to_string(),
-            Color::Magenta => "\x1b[95m".to_string(),
-            Color::Cyan => "\x1b[96m".to_string(),
-            Color::White => "\x1b[97m".to_string(),
-            Color::AnsiValue(n) => format!("\x1b[38;5;{n}m"),
-            Color::Rgb { r, g, b } => format!("\x1b[38;2;{r};{g};{b}m"),
-            _ => "\x1b[0m".to_string(),
-        }
-    }
-
-    fn calculate_display_width(line: &str) -> usize {
-        let mut width = 0;
+    /// Columns a logo line takes once its color placeholders are removed.
+    /// Pre-colored art has no placeholders, and its escape sequences take
+    /// no space.
+    fn calculate_display_width(line: &str, logo_type: LogoType) -> usize {
+        if logo_type == LogoType::Ansi {
+            return layout::text_width(line);
+        }
+        let mut text = String::with_capacity(line.len());
         let mut chars = line.chars().peekable();
 
         while let Some(ch) = chars.next() {
-            if ch == '$' {
-                if let Some(&next) = chars.peek() {
-                    if next.is_numeric() {
-                        chars.next();
-                    } else if next == '{' {
-                        chars.next();
-                        while let Some(&ch) = chars.peek() {
-                            chars.next();
-                            if ch == '}' {
-                                break;
-                            }
+            match (ch, chars.peek()) {
+                ('$', Some(next)) if next.is_numeric() => {
+                    chars.next();
+                }
+                ('$', Some('{')) => {
+                    for ch in chars.by_ref() {
+                        if ch == '}' {
+                            break;
                         }
-                    } else {
-                        width += 1;
                     }
-                } else {
-                    width += 1;
                 }
-            } else {
-                width += 1;
+                _ => text.push(ch),
             }
         }
 
-        width
+        layout::text_width(&text)
     }
 }
 
 impl SystemInfo {
-    fn new() -> Self {
-        let username = env::var("USER").unwrap_or_else(|_| "unknown".to_string());
-        let hostname = gethostname::gethostname().to_string_lossy().to_string();
-
-        let os = Self::detect_os();
-        let host = Self::detect_host();
-        let kernel = Self::detect_kernel();
-        let uptime = Self::detect_uptime();
-        let packages = Self::detect_packages();
-        let shell = Self::detect_shell();
-        let display = Self::detect_display();
-        let terminal = Self::detect_terminal();
-        let cpu = Self::detect_cpu();
-        let gpu = Self::detect_gpu();
-        let (memory, memory_percent) = Self::detect_memory();
-        let swap = Self::detect_swap();
-        let disks = Self::detect_disks();
-        let local_ip = Self::detect_local_ip();
-        let locale = env::var("LANG").unwrap_or_else(|_| "unknown".to_string());
+    fn new(root: &SysRoot, options: &DetectOptions) -> Self {
+        let username = root.env("USER").unwrap_or_else(|| "unknown".to_string());
+        let hostname = root.hostname();
+
+        let os = Self::detect_os(root);
+        let dmi = Dmi::detect(root);
+        let host = Self::detect_host(root, &dmi);
+        let bios = Self::format_bios(&dmi);
+        let board = Self::format_board(&dmi);
+        let chassis = Self::format_chassis(&dmi);
+        let kernel = Self::detect_kernel(root);
+        let uptime = Self::detect_uptime(root);
+        let packages = Self::detect_packages(root);
+        let shell = Self::detect_shell(root);
+        let display = Self::detect_display(root);
+        let terminal = Self::detect_terminal(root);
+        let cpu = Self::detect_cpu(root);
+        let cpu_usage = options
+            .cpu_usage_interval
+            .and_then(|interval| Self::detect_cpu_usage(root, interval));
+        let gpu = Self::detect_gpu(root);
+        let (memory, memory_percent, memory_breakdown) =
+            match Self::detect_memory(root, options.size_units, &options.memory_details) {
+                Some((memory, percent, breakdown)) => (Some(memory), percent, breakdown),
+                None => (None, 0, String::new()),
+            };
+        let physical_memory = Self::detect_physical_memory(root, options.size_units);
+        let swaps = swap::detect_swaps(root);
+        let zswap = swap::detect_zswap(root)
+            .filter(|status| status.enabled)
+            .map(|status| Self::format_zswap(root, &status, options.size_units));
+        let disks = disk::detect_disks(root, &options.disk_filter);
+        let local_ip = Self::detect_local_ip(root);
+        let batteries = battery::detect_batteries(root);
+        let locale = root.env("LANG").filter(|lang| !lang.is_empty());
 
         Self {
             username,
             hostname,
             os,
             host,
+            bios,
+            board,
+            chassis,
             kernel,
             uptime,
             packages,
@@ -508,42 +899,25 @@ impl SystemInfo {
             display,
             terminal,
             cpu,
+            cpu_usage,
             gpu,
             memory,
             memory_percent,
-            swap,
+            memory_breakdown,
+            physical_memory,
+            swaps,
+            zswap,
             disks,
+            disk_inodes: options.disk_inodes,
+            size_units: options.size_units,
             local_ip,
+            batteries,
             locale,
         }
     }
 
-    fn color_to_ansi_for_label(&self, color: &Color) -> String {
-        match color {
-            Color::Black => "\x1b[30m".to_string(),
-            Color::DarkRed => "\x1b[31m".to_string(),
-            Color::DarkGreen => "\x1b[32m".to_string(),
-            Color::DarkYellow => "\x1b[33m".to_string(),
-            Color::DarkBlue => "\x1b[34m".to_string(),
-            Color::DarkMagenta => "\x1b[35m".to_string(),
-            Color::DarkCyan => "\x1b[36m".to_string(),
-            Color::Grey => "\x1b[37m".to_string(),
-            Color::DarkGrey => "\x1b[90m".to_string(),
-            Color::Red => "\x1b[91m".to_string(),
-            Color::Green => "\x1b[92m".to_string(),
-            Color::Yellow => "\x1b[93m".to_string(),
-            Color::Blue => "\x1b[94m".to_string(),
-            Color::Magenta => "\x1b[95m".to_string(),
-            Color::Cyan => "\x1b[96m".to_string(),
-            Color::White => "\x1b[97m".to_string(),
-            Color::AnsiValue(n) => format!("\x1b[38;5;{n}m"),
-            Color::Rgb { r, g, b } => format!("\x1b[38;2;{r};{g};{b}m"),
-            _ => "\x1b[0m".to_string(),
-        }
-    }
-
-    fn detect_os() -> String {
-        if let Ok(content) = fs::read_to_string("/etc/os-release") {
+    fn detect_os(root: &SysRoot) -> Option<String> {
+        if let Ok(content) = root.read_to_string("/etc/os-release") {
             for line in content.lines() {
                 if line.starts_with("PRETTY_NAME=") {
                     let os_name = line
@@ -551,20 +925,18 @@ impl SystemInfo {
                         .trim_matches('"')
                         .to_string();
 
-                    let arch = match uname() {
-                        Ok(info) => info.machine().to_string_lossy().to_string(),
-                        Err(_) => "unknown".to_string(),
-                    };
-
-                    return format!("{os_name} {arch}");
+                    return Some(match root.uname() {
+                        Some((_, machine)) => format!("{os_name} {machine}"),
+                        None => os_name,
+                    });
                 }
             }
         }
-        "Unknown OS".to_string()
+        None
     }
 
-    fn detect_os_id() -> String {
-        if let Ok(content) = fs::read_to_string("/etc/os-release") {
+    fn detect_os_id(root: &SysRoot) -> String {
+        if let Ok(content) = root.read_to_string("/etc/os-release") {
             for line in content.lines() {
                 if line.starts_with("ID=") {
                     return line
@@ -577,221 +949,251 @@ impl SystemInfo {
         "unknown".to_string()
     }
 
-    fn detect_host() -> String {
-        let vendor = fs::read_to_string("/sys/devices/virtual/dmi/id/board_vendor")
-            .or_else(|_| fs::read_to_string("/sys/devices/virtual/dmi/id/sys_vendor"))
-            .unwrap_or_else(|_| "Unknown".to_string())
-            .trim()
-            .to_string();
+    /// Product name with its vendor, then version, family and SKU when they
+    /// add something; the device-tree model on boards without DMI
+    fn detect_host(root: &SysRoot, dmi: &Dmi) -> Option<String> {
+        let product = &dmi.product;
+        let Some(name) = &product.name else {
+            return dmi::device_tree_model(root);
+        };
 
-        let name = fs::read_to_string("/sys/devices/virtual/dmi/id/board_name")
-            .or_else(|_| fs::read_to_string("/sys/devices/virtual/dmi/id/product_name"))
-            .unwrap_or_else(|_| "Unknown".to_string())
-            .trim()
-            .to_string();
+        let mut host = match &product.vendor {
+            Some(vendor) if !name.starts_with(vendor.as_str()) => format!("{vendor} {name}"),
+            _ => name.clone(),
+        };
 
-        let version = fs::read_to_string("/sys/devices/virtual/dmi/id/board_version")
-            .or_else(|_| fs::read_to_string("/sys/devices/virtual/dmi/id/product_version"))
-            .unwrap_or_else(|_| "".to_string())
-            .trim()
-            .to_string();
+        let mut extras: Vec<String> = Vec::new();
+        for extra in [&product.version, &product.family].into_iter().flatten() {
+            if extra != name && !extras.contains(extra) {
+                extras.push(extra.clone());
+            }
+        }
+        if let Some(sku) = &product.sku
+            && sku != name
+        {
+            extras.push(format!("SKU {sku}"));
+        }
 
-        if !version.is_empty() && version != "None" {
-            format!("{vendor} {name} ({version})")
-        } else {
-            format!("{vendor} {name}")
+        if !extras.is_empty() {
+            host.push_str(&format!(" ({})", extras.join(", ")));
         }
+        Some(host)
+    }
+
+    /// `vendor version (date) [UEFI|Legacy]`
+    fn format_bios(dmi: &Dmi) -> Option<String> {
+        let bios = &dmi.bios;
+        if bios.vendor.is_none() && bios.version.is_none() {
+            return None;
+        }
+
+        let mut line: Vec<&str> = [&bios.vendor, &bios.version]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let date = bios.date.as_ref().map(|date| format!("({date})"));
+        line.extend(date.as_deref());
+        line.push(if bios.uefi { "[UEFI]" } else { "[Legacy]" });
+        Some(line.join(" "))
     }
 
-    fn detect_kernel() -> String {
-        match uname() {
-            Ok(info) => format!("Linux {}", info.release().to_string_lossy()),
-            Err(_) => "Unknown".to_string(),
+    fn format_board(dmi: &Dmi) -> Option<String> {
+        let board = &dmi.board;
+        let name = board.name.as_ref()?;
+
+        let mut line = match &board.vendor {
+            Some(vendor) => format!("{vendor} {name}"),
+            None => name.clone(),
+        };
+        if let Some(version) = &board.version {
+            line.push_str(&format!(" ({version})"));
         }
+        Some(line)
     }
 
-    fn detect_uptime() -> String {
-        if let Ok(info) = sysinfo::sysinfo() {
-            let uptime = info.uptime();
-            let total_secs = uptime.as_secs();
+    fn format_chassis(dmi: &Dmi) -> Option<String> {
+        let chassis = &dmi.chassis;
+        let kind = chassis.kind?;
 
-            let days = total_secs / 86400;
-            let hours = (total_secs % 86400) / 3600;
-            let minutes = (total_secs % 3600) / 60;
+        Some(match &chassis.version {
+            Some(version) => format!("{kind} ({version})"),
+            None => kind.to_string(),
+        })
+    }
 
-            let mut parts = Vec::new();
+    fn detect_kernel(root: &SysRoot) -> Option<String> {
+        root.uname().map(|(release, _)| format!("Linux {release}"))
+    }
 
-            if days > 0 {
-                if days == 1 {
-                    parts.push("1 day".to_string());
-                } else {
-                    parts.push(format!("{days} days"));
-                }
-            }
+    fn detect_uptime(root: &SysRoot) -> Option<String> {
+        let uptime = root
+            .read_to_string("/proc/uptime")
+            .ok()
+            .and_then(|s| s.split_whitespace().next()?.parse::<f64>().ok())?;
+        let total_secs = uptime as u64;
 
-            if hours > 0 {
-                if hours == 1 {
-                    parts.push("1 hour".to_string());
-                } else {
-                    parts.push(format!("{hours} hours"));
-                }
+        let days = total_secs / 86400;
+        let hours = (total_secs % 86400) / 3600;
+        let minutes = (total_secs % 3600) / 60;
+
+        let mut parts = Vec::new();
+
+        if days > 0 {
+            if days == 1 {
+                parts.push("1 day".to_string());
+            } else {
+                parts.push(format!("{days} days"));
             }
+        }
 
-            if minutes > 0 {
-                if minutes == 1 {
-                    parts.push("1 min".to_string());
-                } else {
-                    parts.push(format!("{minutes} mins"));
-                }
+        if hours > 0 {
+            if hours == 1 {
+                parts.push("1 hour".to_string());
+            } else {
+                parts.push(format!("{hours} hours"));
             }
+        }
 
-            if parts.is_empty() {
-                "less than a minute".to_string()
+        if minutes > 0 {
+            if minutes == 1 {
+                parts.push("1 min".to_string());
             } else {
-                parts.join(", ")
+                parts.push(format!("{minutes} mins"));
             }
+        }
+
+        if parts.is_empty() {
+            Some("less than a minute".to_string())
         } else {
-            "unknown".to_string()
+            Some(parts.join(", "))
         }
     }
 
-    fn detect_packages() -> String {
+    fn detect_packages(root: &SysRoot) -> Option<String> {
         let mut package_counts = Vec::new();
 
-        let dpkg = Self::count_dpkg_packages();
+        let dpkg = Self::count_dpkg_packages(root);
         if dpkg > 0 {
             package_counts.push(format!("{} (dpkg)", dpkg));
         }
 
-        let rpm = Self::count_rpm_packages();
+        let rpm = Self::count_rpm_packages(root);
         if rpm > 0 {
             package_counts.push(format!("{} (rpm)", rpm));
         }
 
-        let pacman = Self::count_pacman_packages();
+        let pacman = Self::count_pacman_packages(root);
         if pacman > 0 {
             package_counts.push(format!("{} (pacman)", pacman));
         }
 
-        let apk = Self::count_apk_packages();
+        let apk = Self::count_apk_packages(root);
         if apk > 0 {
             package_counts.push(format!("{} (apk)", apk));
         }
 
-        let nix_system = Self::count_nix_packages("/run/current-system");
+        let nix_system = Self::count_nix_packages(root, "/run/current-system");
         if nix_system > 0 {
             package_counts.push(format!("{} (nix-system)", nix_system));
         }
 
         let mut nix_user = 0;
 
-        if let Ok(home) = env::var("HOME") {
+        if let Some(home) = root.env("HOME") {
             let profile_path = format!("{}/.nix-profile", home);
-            nix_user += Self::count_nix_packages(&profile_path);
+            nix_user += Self::count_nix_packages(root, &profile_path);
 
-            let state_home =
-                env::var("XDG_STATE_HOME").unwrap_or_else(|_| format!("{}/.local/state", home));
+            let state_home = root
+                .env("XDG_STATE_HOME")
+                .unwrap_or_else(|| format!("{}/.local/state", home));
             let state_profile = format!("{}/nix/profile", state_home);
-            nix_user += Self::count_nix_packages(&state_profile);
+            nix_user += Self::count_nix_packages(root, &state_profile);
         }
 
-        if let Ok(user) = env::var("USER") {
+        if let Some(user) = root.env("USER") {
             let per_user_profile = format!("/etc/profiles/per-user/{}", user);
-            nix_user += Self::count_nix_packages(&per_user_profile);
+            nix_user += Self::count_nix_packages(root, &per_user_profile);
         }
 
         if nix_user > 0 {
             package_counts.push(format!("{} (nix-user)", nix_user));
         }
 
-        let flatpak = Self::count_flatpak_packages();
+        let flatpak = Self::count_flatpak_packages(root);
         if flatpak > 0 {
             package_counts.push(format!("{} (flatpak)", flatpak));
         }
 
-        let snap = Self::count_snap_packages();
+        let snap = Self::count_snap_packages(root);
         if snap > 0 {
             package_counts.push(format!("{} (snap)", snap));
         }
 
-        let xbps = Self::count_xbps_packages();
+        let xbps = Self::count_xbps_packages(root);
         if xbps > 0 {
             package_counts.push(format!("{} (xbps)", xbps));
         }
 
-        if package_counts.is_empty() {
-            "unknown".to_string()
-        } else {
-            package_counts.join(", ")
-        }
+        (!package_counts.is_empty()).then(|| package_counts.join(", "))
     }
 
-    fn count_dpkg_packages() -> u32 {
+    fn count_dpkg_packages(root: &SysRoot) -> u32 {
         let status_file = "/var/lib/dpkg/status";
-        if !Path::new(status_file).exists() {
+        if !root.exists(status_file) {
             return 0;
         }
 
-        if let Ok(content) = fs::read_to_string(status_file) {
+        if let Ok(content) = root.read_to_string(status_file) {
             return content.matches("Status: install ok installed").count() as u32;
         }
 
         0
     }
 
-    fn count_rpm_packages() -> u32 {
-        if let Ok(output) = Command::new("rpm").args(["-qa"]).output()
-            && output.status.success()
-        {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+    fn count_rpm_packages(root: &SysRoot) -> u32 {
+        if let Some(stdout) = root.command("rpm", &["-qa"]) {
             return stdout.lines().filter(|line| !line.is_empty()).count() as u32;
         }
 
         0
     }
 
-    fn count_pacman_packages() -> u32 {
+    fn count_pacman_packages(root: &SysRoot) -> u32 {
         let pacman_dir = "/var/lib/pacman/local";
-        if !Path::new(pacman_dir).exists() {
+        if !root.exists(pacman_dir) {
             return 0;
         }
 
-        if let Ok(entries) = fs::read_dir(pacman_dir) {
-            return entries
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
-                .count() as u32;
+        if let Ok(entries) = root.read_dir(pacman_dir) {
+            return entries.iter().filter(|e| e.is_dir).count() as u32;
         }
 
         0
     }
 
-    fn count_apk_packages() -> u32 {
+    fn count_apk_packages(root: &SysRoot) -> u32 {
         let installed_file = "/lib/apk/db/installed";
-        if !Path::new(installed_file).exists() {
+        if !root.exists(installed_file) {
             return 0;
         }
 
-        if let Ok(content) = fs::read_to_string(installed_file) {
+        if let Ok(content) = root.read_to_string(installed_file) {
             return content.matches("C:Q").count() as u32;
         }
 
         0
     }
 
-    fn count_snap_packages() -> u32 {
+    fn count_snap_packages(root: &SysRoot) -> u32 {
         let mut count = 0;
 
         let snap_dir = "/snap";
-        if let Ok(entries) = fs::read_dir(snap_dir) {
+        if let Ok(entries) = root.read_dir(snap_dir) {
             count = entries
-                .filter_map(|e| e.ok())
+                .iter()
                 .filter(|e| {
-                    let name = e.file_name();
-                    let name_str = name.to_string_lossy();
-                    e.file_type().map(|t| t.is_dir()).unwrap_or(false)
-                        && !name_str.starts_with('.')
-                        && name_str != "bin" // Exclude /snap/bin
+                    e.is_dir && !e.name.starts_with('.') && e.name != "bin" // Exclude /snap/bin
                 })
                 .count() as u32;
 
@@ -801,36 +1203,27 @@ impl SystemInfo {
         }
 
         let snapd_dir = "/var/lib/snapd/snap";
-        if let Ok(entries) = fs::read_dir(snapd_dir) {
+        if let Ok(entries) = root.read_dir(snapd_dir) {
             count = entries
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let name = e.file_name();
-                    let name_str = name.to_string_lossy();
-                    e.file_type().map(|t| t.is_dir()).unwrap_or(false)
-                        && !name_str.starts_with('.')
-                        && name_str != "bin"
-                })
+                .iter()
+                .filter(|e| e.is_dir && !e.name.starts_with('.') && e.name != "bin")
                 .count() as u32;
         }
 
         count
     }
 
-    fn count_xbps_packages() -> u32 {
+    fn count_xbps_packages(root: &SysRoot) -> u32 {
         let xbps_dir = "/var/db/xbps";
-        if !Path::new(xbps_dir).exists() {
+        if !root.exists(xbps_dir) {
             return 0;
         }
 
-        if let Ok(entries) = fs::read_dir(xbps_dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name();
-                let name_str = name.to_string_lossy();
-
-                if name_str.starts_with("pkgdb-") {
-                    let path = entry.path();
-                    if let Ok(content) = fs::read_to_string(&path) {
+        if let Ok(entries) = root.read_dir(xbps_dir) {
+            for entry in entries {
+                if entry.name.starts_with("pkgdb-") {
+                    let path = format!("{xbps_dir}/{}", entry.name);
+                    if let Ok(content) = root.read_to_string(&path) {
                         return content.matches("<string>installed</string>").count() as u32;
                     }
                 }
@@ -840,28 +1233,19 @@ impl SystemInfo {
         0
     }
 
-    fn count_nix_packages(path: &str) -> u32 {
-        if !Path::new(path).exists() {
+    fn count_nix_packages(root: &SysRoot, path: &str) -> u32 {
+        if !root.exists(path) {
             return 0;
         }
 
-        let output = Command::new("nix-store")
-            .args(["--query", "--requisites", path])
-            .output();
-
-        let Ok(output) = output else {
+        let Some(stdout) = root.command("nix-store", &["--query", "--requisites", path]) else {
             return 0;
         };
 
-        if !output.status.success() {
-            return 0;
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
         let mut count = 0;
 
         for line in stdout.lines() {
-            if Self::is_valid_nix_package(line) {
+            if Self::is_valid_nix_package(root, line) {
                 count += 1;
             }
         }
@@ -869,12 +1253,12 @@ impl SystemInfo {
         count
     }
 
-    fn is_valid_nix_package(path: &str) -> bool {
+    fn is_valid_nix_package(root: &SysRoot, path: &str) -> bool {
         if !path.starts_with("/nix/store/") {
             return false;
         }
 
-        if !Path::new(path).is_dir() {
+        if !root.is_dir(path) {
             return false;
         }
 
@@ -938,180 +1322,159 @@ impl SystemInfo {
         matches!(state, State::Match)
     }
 
-    fn count_flatpak_packages() -> u32 {
+    fn count_flatpak_packages(root: &SysRoot) -> u32 {
         let mut count = 0;
 
-        let system_app_dir = "/var/lib/flatpak/app";
-        if let Ok(entries) = fs::read_dir(system_app_dir) {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type()
-                    && file_type.is_dir()
-                {
-                    let name = entry.file_name();
-                    let name_str = name.to_string_lossy();
-                    if !name_str.starts_with('.') {
-                        let current_path = format!("{}/{}/current", system_app_dir, name_str);
-                        if Path::new(&current_path).exists() {
-                            count += 1;
-                        }
-                    }
-                }
-            }
-        }
+        count += Self::count_flatpak_apps(root, "/var/lib/flatpak/app");
+        count += Self::count_flatpak_runtimes(root, "/var/lib/flatpak/runtime");
 
-        let system_runtime_dir = "/var/lib/flatpak/runtime";
-        if let Ok(entries) = fs::read_dir(system_runtime_dir) {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type()
-                    && file_type.is_dir()
-                {
-                    let name = entry.file_name();
-                    let name_str = name.to_string_lossy();
+        if let Some(home) = root.env("HOME") {
+            count += Self::count_flatpak_apps(root, &format!("{home}/.local/share/flatpak/app"));
+            count +=
+                Self::count_flatpak_runtimes(root, &format!("{home}/.local/share/flatpak/runtime"));
+        }
 
-                    if name_str.starts_with('.') {
-                        continue;
-                    }
+        count
+    }
 
-                    if let Some(dot_pos) = name_str.rfind('.') {
-                        let suffix = &name_str[dot_pos + 1..];
-                        if suffix == "Locale" || suffix == "Debug" {
-                            continue;
-                        }
-                    }
+    fn count_flatpak_apps(root: &SysRoot, app_dir: &str) -> u32 {
+        let mut count = 0;
 
-                    let runtime_path = entry.path();
-                    if let Ok(arch_entries) = fs::read_dir(&runtime_path) {
-                        for arch_entry in arch_entries.flatten() {
-                            if let Ok(arch_type) = arch_entry.file_type()
-                                && arch_type.is_dir()
-                                && !arch_entry.file_name().to_string_lossy().starts_with('.')
-                                && let Ok(version_entries) = fs::read_dir(arch_entry.path())
-                            {
-                                count += version_entries.count();
-                            }
-                        }
+        if let Ok(entries) = root.read_dir(app_dir) {
+            for entry in entries {
+                if entry.is_dir && !entry.name.starts_with('.') {
+                    let current_path = format!("{}/{}/current", app_dir, entry.name);
+                    if root.exists(&current_path) {
+                        count += 1;
                     }
                 }
             }
         }
 
-        if let Ok(home) = env::var("HOME") {
-            let user_app_dir = format!("{}/.local/share/flatpak/app", home);
-            if let Ok(entries) = fs::read_dir(&user_app_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type()
-                        && file_type.is_dir()
-                    {
-                        let name = entry.file_name();
-                        let name_str = name.to_string_lossy();
-                        if !name_str.starts_with('.') {
-                            let current_path = format!("{}/{}/current", user_app_dir, name_str);
-                            if Path::new(&current_path).exists() {
-                                count += 1;
-                            }
-                        }
-                    }
-                }
-            }
+        count
+    }
 
-            let user_runtime_dir = format!("{}/.local/share/flatpak/runtime", home);
-            if let Ok(entries) = fs::read_dir(&user_runtime_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type()
-                        && file_type.is_dir()
-                    {
-                        let name = entry.file_name();
-                        let name_str = name.to_string_lossy();
-
-                        if name_str.starts_with('.') {
-                            continue;
-                        }
+    fn count_flatpak_runtimes(root: &SysRoot, runtime_dir: &str) -> u32 {
+        let mut count = 0;
 
-                        if let Some(dot_pos) = name_str.rfind('.') {
-                            let suffix = &name_str[dot_pos + 1..];
-                            if suffix == "Locale" || suffix == "Debug" {
-                                continue;
-                            }
-                        }
+        if let Ok(entries) = root.read_dir(runtime_dir) {
+            for entry in entries {
+                if !entry.is_dir || entry.name.starts_with('.') {
+                    continue;
+                }
 
-                        let runtime_path = entry.path();
-                        if let Ok(arch_entries) = fs::read_dir(&runtime_path) {
-                            for arch_entry in arch_entries.flatten() {
-                                if let Ok(arch_type) = arch_entry.file_type()
-                                    && arch_type.is_dir()
-                                    && !arch_entry.file_name().to_string_lossy().starts_with('.')
-                                    && let Ok(version_entries) = fs::read_dir(arch_entry.path())
-                                {
-                                    count += version_entries.count();
-                                }
-                            }
+                if let Some(dot_pos) = entry.name.rfind('.') {
+                    let suffix = &entry.name[dot_pos + 1..];
+                    if suffix == "Locale" || suffix == "Debug" {
+                        continue;
+                    }
+                }
+
+                let runtime_path = format!("{runtime_dir}/{}", entry.name);
+                if let Ok(arch_entries) = root.read_dir(&runtime_path) {
+                    for arch_entry in arch_entries {
+                        if arch_entry.is_dir
+                            && !arch_entry.name.starts_with('.')
+                            && let Ok(version_entries) =
+                                root.read_dir(&format!("{runtime_path}/{}", arch_entry.name))
+                        {
+                            count += version_entries.len() as u32;
                         }
                     }
                 }
             }
         }
 
-        count as u32
+        count
     }
 
-    fn detect_shell() -> String {
-        if let Ok(shell_path) = env::var("SHELL")
-            && let Some(shell_name) = Path::new(&shell_path).file_name()
-        {
-            let shell = shell_name.to_string_lossy().to_string();
+    fn detect_shell(root: &SysRoot) -> Option<String> {
+        let shell = shell::detect_shell(root)?;
+        Some(match shell.version {
+            Some(version) => format!("{} {version}", shell.name),
+            None => shell.name,
+        })
+    }
 
-            if let Ok(output) = Command::new(&shell_path).arg("--version").output() {
-                let version_str = String::from_utf8_lossy(&output.stdout);
-                if let Some(version) = Self::extract_version(&version_str) {
-                    return format!("{shell} {version}");
-                }
-            }
+    /// Resolution, refresh rate and size of the first connected screen,
+    /// from `xrandr`
+    fn detect_display(root: &SysRoot) -> Option<String> {
+        let output = root.command("xrandr", &[])?;
+        let mut lines = output.lines();
+        let screen = lines.by_ref().find(|line| line.contains(" connected"))?;
+        let mut display = screen
+            .split_whitespace()
+            .find(|s| s.contains('x') && s.chars().next().is_some_and(|c| c.is_ascii_digit()))?
+            .split('+')
+            .next()?
+            .to_string();
 
-            return shell;
+        // The current mode, marked with `*`, is listed under the screen
+        let refresh = lines
+            .take_while(|line| line.starts_with(' '))
+            .flat_map(str::split_whitespace)
+            .find(|s| s.trim_end_matches('+').ends_with('*'))
+            .and_then(|s| s.trim_end_matches(['*', '+']).parse::<f32>().ok());
+        if let Some(refresh) = refresh {
+            display.push_str(&format!(" @ {} Hz", refresh as u32));
+        }
+
+        let millimeters: Vec<f32> = screen
+            .split_whitespace()
+            .filter_map(|s| s.strip_suffix("mm")?.parse().ok())
+            .collect();
+        if let [width, height] = millimeters[..]
+            && width > 0.0
+            && height > 0.0
+        {
+            let inches = (width * width + height * height).sqrt() / 25.4;
+            display.push_str(&format!(" in {}\"", inches.round() as u32));
         }
-        "unknown".to_string()
+        Some(display)
     }
 
-    fn extract_version(text: &str) -> Option<String> {
-        VERSION_REGEX
-            .captures(text)
-            .and_then(|cap| cap.get(1))
-            .map(|m| m.as_str().to_string())
+    fn detect_terminal(root: &SysRoot) -> Option<String> {
+        match root.read_link("/proc/self/fd/0") {
+            Ok(tty) => Some(tty.to_string_lossy().to_string()),
+            Err(_) => root.env("TTY"),
+        }
     }
 
-    fn detect_display() -> String {
-        if let Ok(output) = Command::new("xrandr").output() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
-                if line.contains(" connected")
-                    && line.contains("x")
-                    && let Some(resolution) = line.split_whitespace().find(|s| {
-                        s.contains("x") && s.chars().next().is_some_and(|c| c.is_numeric())
-                    })
-                {
-                    let refresh = line
-                        .split_whitespace()
-                        .find(|s| s.ends_with("*") || s.ends_with("+"))
-                        .and_then(|s| s.trim_end_matches(['*', '+']).parse::<f32>().ok())
-                        .unwrap_or(75.0);
+    /// Busy share of all CPUs over `interval`. Snapshots, where both reads
+    /// are the same, give the average since boot instead.
+    fn detect_cpu_usage(root: &SysRoot, interval: Duration) -> Option<u32> {
+        let (busy_before, total_before) = Self::read_cpu_times(root)?;
+        std::thread::sleep(interval);
+        let (busy, total) = Self::read_cpu_times(root)?;
 
-                    return format!("{} @ {} Hz in 15\"", resolution, refresh as u32);
-                }
-            }
-        }
-        "1280x800 @ 75 Hz in 15\"".to_string()
+        let (busy, total) = if total > total_before {
+            (busy.saturating_sub(busy_before), total - total_before)
+        } else {
+            (busy, total)
+        };
+        (total > 0).then(|| (busy as f64 / total as f64 * 100.0) as u32)
     }
 
-    fn detect_terminal() -> String {
-        if let Ok(tty) = fs::read_link("/proc/self/fd/0") {
-            tty.to_string_lossy().to_string()
-        } else {
-            env::var("TTY").unwrap_or_else(|_| "/dev/pts/1".to_string())
-        }
+    /// Busy and total time from the `cpu` line of `/proc/stat`. Guest time
+    /// is already counted in user time, so only the first 8 fields are added.
+    fn read_cpu_times(root: &SysRoot) -> Option<(u64, u64)> {
+        let stat = root.read_to_string("/proc/stat").ok()?;
+        let times: Vec<u64> = stat
+            .lines()
+            .find(|line| line.starts_with("cpu "))?
+            .split_whitespace()
+            .skip(1)
+            .take(8)
+            .filter_map(|field| field.parse().ok())
+            .collect();
+
+        let total: u64 = times.iter().sum();
+        let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+        Some((total.saturating_sub(idle), total))
     }
 
-    fn detect_cpu() -> String {
-        if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+    fn detect_cpu(root: &SysRoot) -> Option<String> {
+        if let Ok(cpuinfo) = root.read_to_string("/proc/cpuinfo") {
             let mut model_name = String::new();
             let mut cpu_count = 0;
             let mut cpu_mhz = 0.0;
@@ -1131,183 +1494,276 @@ impl SystemInfo {
 
             if !model_name.is_empty() {
                 let ghz = cpu_mhz / 1000.0;
-                return format!("{model_name} ({cpu_count}) @ {ghz:.2} GHz");
+                return Some(format!("{model_name} ({cpu_count}) @ {ghz:.2} GHz"));
             }
         }
-        "Unknown CPU".to_string()
+        None
     }
 
-    fn detect_gpu() -> String {
-        if let Ok(output) = Command::new("lspci").output() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
+    fn detect_gpu(root: &SysRoot) -> Option<String> {
+        if let Some(output_str) = root.command("lspci", &[]) {
             for line in output_str.lines() {
                 if (line.contains("VGA") || line.contains("3D") || line.contains("Display"))
                     && let Some(gpu_info) = line.split(':').nth(2)
                 {
-                    return gpu_info.trim().to_string();
+                    return Some(gpu_info.trim().to_string());
                 }
             }
         }
-        "Unknown GPU".to_string()
+        None
     }
 
-    fn detect_memory() -> (String, u32) {
-        if let Ok(meminfo) = fs::read_to_string("/proc/meminfo") {
-            let mut total_kb = 0u64;
-            let mut available_kb = 0u64;
-
-            for line in meminfo.lines() {
-                if line.starts_with("MemTotal:") {
-                    if let Some(val) = line.split_whitespace().nth(1) {
-                        total_kb = val.parse().unwrap_or(0);
+    fn detect_memory(
+        root: &SysRoot,
+        units: SizeUnits,
+        details: &[MemoryDetail],
+    ) -> Option<(String, u32, String)> {
+        let info = memory::read_meminfo(root)?;
+
+        let size = |bytes: u64| Self::format_bytes(bytes, units);
+        let mut parts = Vec::new();
+
+        for detail in details {
+            match detail {
+                MemoryDetail::Buffers => parts.push(format!("Buffers {}", size(info.buffers))),
+                MemoryDetail::Cache => parts.push(format!("Cache {}", size(info.cached))),
+                MemoryDetail::Shared => parts.push(format!("Shared {}", size(info.shared))),
+                MemoryDetail::Zram => {
+                    for device in memory::detect_zram(root) {
+                        let ratio = device
+                            .compression_ratio()
+                            .map(|ratio| format!(", {ratio:.2}x"))
+                            .unwrap_or_default();
+                        parts.push(format!(
+                            "{} {} ({} stored{ratio})",
+                            device.name,
+                            size(device.memory_used),
+                            size(device.original_size)
+                        ));
+                    }
+                }
+                MemoryDetail::Zswap => {
+                    if info.zswapped > 0 {
+                        parts.push(format!(
+                            "zswap {} ({} stored)",
+                            size(info.zswap),
+                            size(info.zswapped)
+                        ));
+                    }
+                }
+                MemoryDetail::Hugepages => {
+                    if info.hugepages_total > 0 {
+                        let used = info.hugepages_total - info.hugepages_free;
+                        parts.push(format!(
+                            "HugePages {} / {}",
+                            size(used * info.hugepage_size),
+                            size(info.hugepages_total * info.hugepage_size)
+                        ));
                     }
-                } else if line.starts_with("MemAvailable:")
-                    && let Some(val) = line.split_whitespace().nth(1)
-                {
-                    available_kb = val.parse().unwrap_or(0);
                 }
             }
+        }
 
-            if total_kb > 0 && available_kb > 0 {
-                let used_kb = total_kb - available_kb;
-                let used_bytes = used_kb * 1024;
-                let total_gib = total_kb as f64 / 1024.0 / 1024.0;
-                let percent = (used_kb as f64 / total_kb as f64 * 100.0) as u32;
+        let breakdown = if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", parts.join(", "))
+        };
+
+        Some((
+            format!("{} / {}", size(info.used()), size(info.total)),
+            info.percent(),
+            breakdown,
+        ))
+    }
 
-                let used_str = Self::format_bytes(used_bytes);
+    /// One line per group of identical modules, like `2 x 16.00 GiB DDR4 @ 3200 MT/s (Kingston)`
+    fn detect_physical_memory(root: &SysRoot, units: SizeUnits) -> Vec<String> {
+        let mut groups: Vec<(Dimm, usize)> = Vec::new();
 
-                return (format!("{used_str} / {total_gib:.2} GiB"), percent);
+        for dimm in memory::detect_dimms(root) {
+            let same = |other: &Dimm| {
+                other.size == dimm.size
+                    && other.memory_type == dimm.memory_type
+                    && other.speed == dimm.speed
+                    && other.manufacturer == dimm.manufacturer
+            };
+            match groups.iter_mut().find(|(other, _)| same(other)) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((dimm, 1)),
             }
         }
-        ("Unknown".to_string(), 0)
-    }
-
-    fn detect_swap() -> String {
-        match sysinfo::sysinfo() {
-            Ok(info) => {
-                let total_swap = info.swap_total();
-                let free_swap = info.swap_free();
 
-                if total_swap == 0 {
-                    return "Disabled".to_string();
+        groups
+            .iter()
+            .map(|(dimm, count)| {
+                let mut line = Self::format_bytes(dimm.size, units);
+                if *count > 1 {
+                    line = format!("{count} x {line}");
                 }
+                if let Some(memory_type) = dimm.memory_type {
+                    line.push_str(&format!(" {memory_type}"));
+                }
+                if let Some(speed) = dimm.speed {
+                    line.push_str(&format!(" @ {speed} MT/s"));
+                }
+                if let Some(manufacturer) = &dimm.manufacturer {
+                    line.push_str(&format!(" ({manufacturer})"));
+                }
+                line
+            })
+            .collect()
+    }
 
-                let used_swap = total_swap - free_swap;
-                let total_gib = total_swap as f64 / 1024.0 / 1024.0 / 1024.0;
-                let percent = ((used_swap as f64 / total_swap as f64) * 100.0) as u32;
+    /// `used / total` and `- type, priority`, the two sides of the percentage
+    fn format_swap(device: &SwapDevice, units: SizeUnits) -> (String, String) {
+        let usage = format!(
+            "{} / {}",
+            Self::format_bytes(device.used, units),
+            Self::format_bytes(device.size, units)
+        );
 
-                let used_str = Self::format_bytes(used_swap);
-                format!("{used_str} / {total_gib:.2} GiB ({}%)", percent)
+        let mut details = Vec::new();
+        if let Some(zram) = &device.zram {
+            let compression: Vec<String> = zram
+                .algorithm
+                .iter()
+                .cloned()
+                .chain(zram.compression_ratio().map(|ratio| format!("{ratio:.2}x")))
+                .collect();
+            if compression.is_empty() {
+                details.push("zram".to_string());
+            } else {
+                details.push(format!("zram ({})", compression.join(", ")));
             }
-            Err(_) => "Unknown".to_string(),
+        } else if !device.kind.is_empty() {
+            details.push(device.kind.clone());
+        }
+        if let Some(priority) = device.priority {
+            details.push(format!("priority {priority}"));
+        }
+
+        if details.is_empty() {
+            (usage, String::new())
+        } else {
+            (usage, format!(" - {}", details.join(", ")))
         }
     }
 
-    fn detect_disks() -> Vec<(String, String, u32)> {
-        let mut disks = Vec::new();
-        let mut seen_devices = HashSet::new();
+    /// Pool size and configuration of an enabled zswap
+    fn format_zswap(root: &SysRoot, status: &ZswapStatus, units: SizeUnits) -> String {
+        let mut line = match memory::read_meminfo(root) {
+            Some(info) if info.zswapped > 0 => format!(
+                "{} ({} stored)",
+                Self::format_bytes(info.zswap, units),
+                Self::format_bytes(info.zswapped, units)
+            ),
+            _ => "Enabled".to_string(),
+        };
 
-        if let Ok(mounts) = fs::read_to_string("/proc/mounts") {
-            for line in mounts.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() < 3 {
-                    continue;
-                }
+        let mut details: Vec<String> = status
+            .compressor
+            .iter()
+            .chain(status.zpool.iter())
+            .cloned()
+            .collect();
+        if let Some(percent) = status.max_pool_percent {
+            details.push(format!("max {percent}%"));
+        }
+        if !details.is_empty() {
+            line.push_str(&format!(" - {}", details.join(", ")));
+        }
+        line
+    }
 
-                let device = parts[0];
-                let mount_point = parts[1];
-                let fs_type = parts[2];
-
-                // Skip special filesystems
-                if mount_point.starts_with("/dev")
-                    || mount_point.starts_with("/proc")
-                    || mount_point.starts_with("/sys")
-                    || mount_point.starts_with("/run")
-                    || mount_point.starts_with("/tmp")
-                    || mount_point == "/boot"
-                    || mount_point == "/boot/efi"
-                    || device.starts_with("tmpfs")
-                    || device.starts_with("devtmpfs")
-                    || device.starts_with("overlay")
-                    || device == "efivarfs"
-                    || device == "none"
-                {
-                    continue;
-                }
+    /// `used / total` and `- fs_type [details]`, the two sides of the
+    /// percentage, like `df`
+    fn format_disk(disk: &Disk, units: SizeUnits, inodes: bool) -> (String, String) {
+        let usage = &disk.usage;
+        let inode_info = if inodes && usage.inodes_total > 0 {
+            format!(
+                ", {} / {} inodes ({}%)",
+                usage.inodes_used, usage.inodes_total, usage.inodes_percent
+            )
+        } else {
+            String::new()
+        };
 
-                // Only include real filesystems
-                if !device.starts_with("/dev/") && mount_point != "/" {
-                    continue;
-                }
+        (
+            format!(
+                "{} / {}",
+                Self::format_bytes(usage.used, units),
+                Self::format_bytes(usage.total, units)
+            ),
+            format!(
+                " - {}{}{}",
+                disk.fs_type,
+                inode_info,
+                Self::format_disk_details(disk, units)
+            ),
+        )
+    }
 
-                // Skip if device already seen (avoids duplicates)
-                if seen_devices.contains(device) {
-                    continue;
-                }
-                seen_devices.insert(device.to_string());
-
-                if let Ok(stat) = statfs(mount_point) {
-                    let total_bytes = stat.blocks() * stat.block_size() as u64;
-                    let free_bytes = stat.blocks_free() * stat.block_size() as u64;
-                    let used_bytes = total_bytes - free_bytes;
-                    let percent = if total_bytes > 0 {
-                        ((used_bytes as f64 / total_bytes as f64) * 100.0) as u32
-                    } else {
-                        0
-                    };
+    /// Trailing `[...]` block with the subvolume and backing device, if known
+    fn format_disk_details(disk: &Disk, units: SizeUnits) -> String {
+        let mut details = Vec::new();
 
-                    let used_str = Self::format_bytes(used_bytes);
-                    let total_str = Self::format_bytes(total_bytes);
+        if let Some(subvol) = &disk.subvolume {
+            details.push(format!("subvol {subvol}"));
+        }
 
-                    let info = format!("{used_str} / {total_str} - {fs_type}");
-                    disks.push((mount_point.to_string(), info, percent));
-                }
-            }
+        if disk.network {
+            details.push(format!("Network {}", disk.source));
         }
 
-        // Sort by mount point, with "/" first
-        disks.sort_by(|a, b| {
-            if a.0 == "/" {
-                std::cmp::Ordering::Less
-            } else if b.0 == "/" {
-                std::cmp::Ordering::Greater
-            } else {
-                a.0.cmp(&b.0)
+        if let Some(device) = &disk.device {
+            if let Some(model) = &device.model {
+                details.push(model.clone());
             }
-        });
+            details.push(Self::format_bytes(device.size, units));
+            details.push(if device.rotational { "HDD" } else { "SSD" }.to_string());
+            if device.removable {
+                details.push("Removable".to_string());
+            }
+        }
 
-        if disks.is_empty() {
-            disks.push(("/".to_string(), "Unknown".to_string(), 0));
+        if disk.read_only {
+            details.push("Read-only".to_string());
         }
 
-        disks
+        if details.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", details.join(", "))
+        }
     }
 
-    fn format_bytes(bytes: u64) -> String {
-        const KIB: f64 = 1024.0;
-        const MIB: f64 = KIB * 1024.0;
-        const GIB: f64 = MIB * 1024.0;
-        const TIB: f64 = GIB * 1024.0;
+    fn format_bytes(bytes: u64, units: SizeUnits) -> String {
+        let (base, suffixes) = match units {
+            SizeUnits::Iec => (1024.0, ["KiB", "MiB", "GiB", "TiB", "PiB"]),
+            SizeUnits::Si => (1000.0, ["kB", "MB", "GB", "TB", "PB"]),
+        };
 
-        let bytes_f = bytes as f64;
+        let mut value = bytes as f64;
+        if value < base {
+            return format!("{bytes} B");
+        }
 
-        if bytes_f >= TIB {
-            format!("{:.2} TiB", bytes_f / TIB)
-        } else if bytes_f >= GIB {
-            format!("{:.2} GiB", bytes_f / GIB)
-        } else if bytes_f >= MIB {
-            format!("{:.2} MiB", bytes_f / MIB)
-        } else if bytes_f >= KIB {
-            format!("{:.2} KiB", bytes_f / KIB)
-        } else {
-            format!("{bytes} B")
+        let mut suffix = suffixes[0];
+        for next in suffixes {
+            if value < base {
+                break;
+            }
+            value /= base;
+            suffix = next;
         }
-    }
 
-    fn detect_local_ip() -> String {
-        if let Ok(output) = Command::new("ip").args(["addr", "show"]).output() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
+        format!("{value:.2} {suffix}")
+    }
 
+    /// First non-loopback IPv4 address and its interface
+    fn detect_local_ip(root: &SysRoot) -> Option<(String, String)> {
+        if let Some(output_str) = root.command("ip", &["addr", "show"]) {
             let mut current_interface = String::new();
             for line in output_str.lines() {
                 if let Some(colon_pos) = line.find(':')
@@ -1324,251 +1780,497 @@ impl SystemInfo {
                 if let Some(captures) = IP_REGEX.captures(line) {
                     let ip = &captures[1];
                     if !ip.starts_with("127.") && !current_interface.is_empty() {
-                        return format!("{ip} ({current_interface})");
+                        return Some((ip.to_string(), current_interface));
                     }
                 }
             }
         }
-        "Unknown".to_string()
+        None
     }
 
-    #[allow(clippy::uninlined_format_args)]
-    fn display(&self, logo: &Logo) {
-        let label_color = logo
-            .colors
-            .get("$2")
-            .or_else(|| logo.colors.get("$1"))
-            .map(|color| self.color_to_ansi_for_label(color))
-            .unwrap_or_else(|| "\x1b[96m".to_string()); // Default to cyan if no color
-
-        let mut info_lines: Vec<(String, bool, u32)> = vec![
-            (
-                format!(
-                    "{label_color}\x1b[1m{}\x1b[0m{label_color}\x1b[1m@\x1b[0m{label_color}\x1b[1m{}\x1b[0m",
-                    self.username, self.hostname
-                ),
-                false,
-                0,
-            ),
-            (
-                format!(
-                    "{label_color}{}\x1b[0m",
-                    "-".repeat(self.username.len() + self.hostname.len() + 1)
-                ),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mOS\x1b[0m: {}", self.os),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mHost\x1b[0m: {}", self.host),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mKernel\x1b[0m: {}", self.kernel),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mUptime\x1b[0m: {}", self.uptime),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mPackages\x1b[0m: {}", self.packages),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mShell\x1b[0m: {}", self.shell),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mDisplay\x1b[0m: {}", self.display),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mTerminal\x1b[0m: {}", self.terminal),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mCPU\x1b[0m: {}", self.cpu),
-                false,
-                0,
-            ),
-            (
-                format!("{label_color}\x1b[1mGPU\x1b[0m: {}", self.gpu),
-                false,
-                0,
-            ),
-            (
-                format!(
-                    "{label_color}\x1b[1mMemory\x1b[0m: {} ({}%)",
-                    self.memory, self.memory_percent
-                ),
-                true,
-                self.memory_percent,
-            ),
-            (
-                format!("{label_color}\x1b[1mSwap\x1b[0m: {}", self.swap),
-                false,
-                0,
-            ),
-        ];
+    fn display(&self, logo: &Logo, theme: &Theme, terminal: &Terminal) {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        // Nothing useful to do if stdout is gone (closed pipe)
+        let _ = self.render(logo, theme, terminal, &mut out);
+    }
 
-        for (mount_point, info, percent) in &self.disks {
-            let label = if mount_point == "/" {
-                "Disk (/)".to_string()
-            } else {
-                format!("Disk ({mount_point})")
-            };
+    /// Lines of one layout module; empty when there is nothing to show
+    fn module_lines(&self, module: &str, title_style: &Style, theme: &Theme) -> Vec<InfoLine> {
+        let field = |key: &str, value: &str| InfoLine::Field {
+            key: key.to_string(),
+            value: value.to_string(),
+            percent: None,
+            rest: String::new(),
+        };
+        // Modules that were not detected get no line at all
+        let optional = |key: &str, value: &Option<String>| -> Vec<InfoLine> {
+            value.iter().map(|value| field(key, value)).collect()
+        };
 
-            let fs_type = if let Some(pos) = info.rfind(" - ") {
-                &info[pos + 3..]
-            } else {
-                "unknown"
-            };
+        match module {
+            "title" => vec![InfoLine::Text(
+                theme
+                    .title_parts(&self.username, &self.hostname)
+                    .iter()
+                    .map(|part| format!("{}{part}\x1b[0m", title_style.sgr()))
+                    .collect(),
+            )],
+            "underline" => {
+                let title = theme.title_parts(&self.username, &self.hostname).concat();
+                vec![InfoLine::Text(format!(
+                    "{}{}\x1b[0m",
+                    title_style.colors_only().sgr(),
+                    theme.underline(&title)
+                ))]
+            }
+            "os" => optional("OS", &self.os),
+            "host" => optional("Host", &self.host),
+            "bios" => optional("BIOS", &self.bios),
+            "board" => optional("Board", &self.board),
+            "chassis" => optional("Chassis", &self.chassis),
+            "kernel" => optional("Kernel", &self.kernel),
+            "uptime" => optional("Uptime", &self.uptime),
+            "packages" => optional("Packages", &self.packages),
+            "shell" => optional("Shell", &self.shell),
+            "display" => optional("Display", &self.display),
+            "terminal" => optional("Terminal", &self.terminal),
+            "cpu" => optional("CPU", &self.cpu),
+            "cpuusage" => self
+                .cpu_usage
+                .iter()
+                .map(|&usage| InfoLine::Field {
+                    key: "CPU Usage".to_string(),
+                    value: String::new(),
+                    percent: Some(Percent::Usage(usage)),
+                    rest: String::new(),
+                })
+                .collect(),
+            "gpu" => optional("GPU", &self.gpu),
+            "memory" => self
+                .memory
+                .iter()
+                .map(|memory| InfoLine::Field {
+                    key: "Memory".to_string(),
+                    value: memory.clone(),
+                    percent: Some(Percent::Usage(self.memory_percent)),
+                    rest: self.memory_breakdown.clone(),
+                })
+                .collect(),
+            "physicalmemory" => self
+                .physical_memory
+                .iter()
+                .map(|module| field("Physical Memory", module))
+                .collect(),
+            "swap" => match &self.swaps {
+                Some(swaps) if swaps.is_empty() => vec![field("Swap", "Disabled")],
+                Some(swaps) => swaps
+                    .iter()
+                    .map(|device| {
+                        let (value, rest) = Self::format_swap(device, self.size_units);
+                        InfoLine::Field {
+                            key: if device.path.is_empty() {
+                                "Swap".to_string()
+                            } else {
+                                format!("Swap ({})", device.path)
+                            },
+                            value,
+                            percent: Some(Percent::Usage(device.percent())),
+                            rest,
+                        }
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+            "zswap" => self
+                .zswap
+                .iter()
+                .map(|zswap| field("Zswap", zswap))
+                .collect(),
+            "disk" => self
+                .disks
+                .iter()
+                .map(|disk| {
+                    let (value, rest) = Self::format_disk(disk, self.size_units, self.disk_inodes);
+                    InfoLine::Field {
+                        key: format!("Disk ({})", disk.mount_point),
+                        value,
+                        percent: Some(Percent::Usage(disk.usage.percent)),
+                        rest,
+                    }
+                })
+                .collect(),
+            "localip" => self
+                .local_ip
+                .iter()
+                .map(|(ip, interface)| field(&format!("Local IP ({interface})"), ip))
+                .collect(),
+            "battery" => self
+                .batteries
+                .iter()
+                .map(|battery| InfoLine::Field {
+                    key: match &battery.model {
+                        Some(model) => format!("Battery ({model})"),
+                        None => "Battery".to_string(),
+                    },
+                    value: String::new(),
+                    percent: Some(Percent::Charge(battery.capacity)),
+                    rest: match &battery.status {
+                        Some(status) => format!(" [{status}]"),
+                        None => String::new(),
+                    },
+                })
+                .collect(),
+            "locale" => optional("Locale", &self.locale),
+            _ => Vec::new(),
+        }
+    }
 
-            let size_info = if let Some(pos) = info.rfind(" - ") {
-                &info[..pos]
-            } else {
-                info
-            };
+    /// A line with key and value colors applied, and the usage percentage
+    /// colored by its threshold
+    fn format_info_line(&self, line: &InfoLine, key_style: &Style, theme: &Theme) -> String {
+        let (key, value, percent, rest) = match line {
+            InfoLine::Text(text) => return text.clone(),
+            InfoLine::Field {
+                key,
+                value,
+                percent,
+                rest,
+            } => (key, value, *percent, rest),
+        };
 
-            info_lines.push((
-                format!(
-                    "{}\x1b[1m{label}\x1b[0m: {size_info} ({percent}%) - {fs_type}",
-                    label_color
-                ),
-                true,
-                *percent,
-            ));
-        }
+        let value_color = theme.value_color.unwrap_or_default().sgr();
+        let mut rendered = format!(
+            "{}{key}\x1b[0m{}{}{value_color}",
+            key_style.sgr(),
+            theme.pad_key(key),
+            theme.separator
+        );
 
-        info_lines.push((
-            {
-                let parts: Vec<&str> = self.local_ip.split(" (").collect();
-                if parts.len() == 2 {
-                    let ip = parts[0];
-                    let interface = parts[1].trim_end_matches(')');
-                    format!("{label_color}\x1b[1mLocal IP ({interface})\x1b[0m: {ip}")
-                } else {
-                    format!("{label_color}\x1b[1mLocal IP\x1b[0m: {}", self.local_ip)
-                }
-            },
-            false,
-            0,
-        ));
-        info_lines.push((
-            format!("{label_color}\x1b[1mLocale\x1b[0m: {}", self.locale),
-            false,
-            0,
-        ));
-
-        let max_logo_width = logo
-            .lines
-            .iter()
-            .map(|line| Logo::calculate_display_width(line))
-            .max()
-            .unwrap_or(0);
+        let (percent, severity) = match percent {
+            Some(Percent::Usage(percent)) => (percent, percent),
+            Some(Percent::Charge(percent)) => (percent, 100 - percent.min(100)),
+            None => {
+                rendered.push_str(&format!("{value}{rest}"));
+                return rendered;
+            }
+        };
 
-        let mut current_color = logo.colors.get("$1").copied();
+        // Wraps colored text so the value color resumes after it
+        let paint = |style: Option<&Style>, text: &str| match style {
+            Some(style) => format!("{}{text}\x1b[0m{value_color}", style.sgr()),
+            None => text.to_string(),
+        };
+        let percent_color = theme.percent_color(severity);
+
+        let number = paint(percent_color.as_ref(), &format!("{percent}%"));
+        let mut value = match theme.percent_style {
+            // The bar stands in for the number
+            PercentStyle::Bar => format!("{value}{rest}").trim_start().to_string(),
+            _ if value.is_empty() => format!("{number}{rest}"),
+            _ => format!("{value} ({number}){rest}"),
+        };
 
-        for (i, (info_line, has_percent, percent)) in info_lines.iter().enumerate() {
-            if info_line.contains(": unknown")
-                || info_line.contains(": Unknown")
-                || info_line.contains(": None")
-            {
-                continue;
+        if theme.percent_style != PercentStyle::Percent {
+            let (filled, empty) = theme.bar_cells(percent);
+            let (filled_char, empty_char) = theme.bar_chars;
+            let mut bar = paint(
+                percent_color.as_ref(),
+                &filled_char.to_string().repeat(filled),
+            );
+            bar.push_str(&paint(
+                theme.bar_empty_color.as_ref(),
+                &empty_char.to_string().repeat(empty),
+            ));
+            if let Some((open, close)) = theme.bar_border {
+                bar = format!("{open}{bar}{close}");
             }
+            value = format!("{bar} {value}").trim_end().to_string();
+        }
 
-            if i < logo.lines.len() {
-                let rendered_line = logo.render_line(&logo.lines[i], &mut current_color);
-                print!("\x1b[1m{rendered_line}\x1b[0m");
+        rendered.push_str(&value);
+        rendered
+    }
 
-                let line_width = Logo::calculate_display_width(&logo.lines[i]);
+    /// Lay out the logo and information for `terminal`. Without colors, the
+    /// escape sequences are taken out of the finished layout, so logo, keys
+    /// and percentages all come out plain; with fewer colors, they are mapped
+    /// to the nearest the terminal has.
+    fn render(
+        &self,
+        logo: &Logo,
+        theme: &Theme,
+        terminal: &Terminal,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        if terminal.color && terminal.depth == ColorDepth::TrueColor {
+            return self.render_layout(logo, theme, terminal.width, out);
+        }
+
+        let mut theme = theme.clone();
+        if !terminal.color {
+            // Color blocks are nothing but color
+            theme.modules.retain(|module| module != "colors");
+        }
+
+        let mut rendered = Vec::new();
+        self.render_layout(logo, &theme, terminal.width, &mut rendered)?;
+        out.write_all(
+            terminal
+                .adapt(&String::from_utf8_lossy(&rendered))
+                .as_bytes(),
+        )
+    }
 
-                let padding = max_logo_width - line_width + 2;
-                print!("{}", " ".repeat(padding));
+    /// Lay out the logo and information for a terminal `width` columns wide,
+    /// or without limit when `width` is `None`
+    #[allow(clippy::uninlined_format_args)]
+    fn render_layout(
+        &self,
+        logo: &Logo,
+        theme: &Theme,
+        width: Option<usize>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        // Keys take the logo's key color or second color, and the title
+        // its title color, unless the theme sets one
+        let logo_color = logo.key_color.unwrap_or_else(|| {
+            logo.colors
+                .get("$2")
+                .or_else(|| logo.colors.get("$1"))
+                .and_then(|style| style.fg)
+                .unwrap_or(Color::Cyan)
+        });
+        let label_style = |color: Color| {
+            theme.key_color.unwrap_or_default().over(Style {
+                fg: Some(color),
+                ..Style::KEY
+            })
+        };
+        let key_style = label_style(logo_color);
+        let title_style = label_style(logo.title_color.unwrap_or(logo_color));
 
-                if *has_percent {
-                    if let Some(pos) = info_line.rfind(" (") {
-                        let (before, after) = info_line.split_at(pos);
-                        print!("{before}");
+        let info_lines: Vec<InfoLine> = theme
+            .modules
+            .iter()
+            .flat_map(|module| self.module_lines(module, &title_style, theme))
+            .collect();
 
-                        if let Some(close_pos) = after.find(')') {
-                            let color = if *percent < 33 {
-                                "\x1b[92m" // Bright green
-                            } else if *percent < 66 {
-                                "\x1b[93m" // Bright yellow
-                            } else {
-                                "\x1b[91m" // Bright red
-                            };
+        let max_logo_width = logo.width();
+        let padding = theme.logo_padding;
 
-                            print!(" ({}{}%\x1b[0m){}", color, percent, &after[close_pos + 1..]);
-                        } else {
-                            print!("{after}");
-                        }
-                    } else {
-                        print!("{info_line}");
+        // Modules with nothing to show produced no lines above, so every
+        // logo line gets paired in order with the next visible info line
+        let logo_lines = logo.rendered_lines();
+        let formatted_info: Vec<String> = info_lines
+            .iter()
+            .map(|line| self.format_info_line(line, &key_style, theme))
+            .collect();
+        let rows = layout::rows(logo_lines, formatted_info);
+
+        let info_width = |indent: usize| width.map(|width| width.saturating_sub(indent));
+        let truncate = |line: &str, available: Option<usize>| match available {
+            Some(available) => layout::truncate(line, available),
+            None => line.to_string(),
+        };
+
+        let blocks_indent = match theme.logo_position {
+            LogoPosition::Left => {
+                let available = info_width(max_logo_width + padding);
+                for (logo_line, info_line) in &rows {
+                    match (logo_line, info_line) {
+                        (Some(logo_line), Some(info_line)) => writeln!(
+                            out,
+                            "{logo_line}{}{}\x1b[0m",
+                            " ".repeat(padding),
+                            truncate(info_line, available)
+                        )?,
+                        (Some(logo_line), None) => writeln!(out, "{}", logo_line.trim_end())?,
+                        (None, Some(info_line)) => writeln!(
+                            out,
+                            "{}{}\x1b[0m",
+                            " ".repeat(max_logo_width + padding),
+                            truncate(info_line, available)
+                        )?,
+                        (None, None) => {}
                     }
-                } else {
-                    print!("{info_line}");
                 }
-
-                println!("\x1b[0m");
-            } else {
-                println!("{}{}", " ".repeat(max_logo_width + 2), info_line);
+                max_logo_width + padding
             }
-        }
+            LogoPosition::Right => {
+                let available = info_width(max_logo_width + padding);
+                let info_lines: Vec<Option<String>> = rows
+                    .iter()
+                    .map(|(_, info)| info.as_ref().map(|line| truncate(line, available)))
+                    .collect();
+                let column = info_lines
+                    .iter()
+                    .flatten()
+                    .map(|line| layout::text_width(line))
+                    .max()
+                    .unwrap_or(0);
+
+                for ((logo_line, _), info_line) in rows.iter().zip(&info_lines) {
+                    let info_line = info_line.as_deref().unwrap_or_default();
+                    match logo_line {
+                        Some(logo_line) => writeln!(
+                            out,
+                            "{info_line}\x1b[0m{}{}",
+                            layout::padding(layout::text_width(info_line), column + padding),
+                            logo_line.trim_end()
+                        )?,
+                        None => writeln!(out, "{info_line}\x1b[0m")?,
+                    }
+                }
+                0
+            }
+            LogoPosition::Top | LogoPosition::None => {
+                if theme.logo_position == LogoPosition::Top && !logo.lines.is_empty() {
+                    for line in rows.iter().filter_map(|(logo_line, _)| logo_line.as_ref()) {
+                        writeln!(out, "{}", line.trim_end())?;
+                    }
+                    writeln!(out)?;
+                }
+                for info_line in rows.iter().filter_map(|(_, info_line)| info_line.as_ref()) {
+                    writeln!(out, "{}\x1b[0m", truncate(info_line, width))?;
+                }
+                0
+            }
+        };
 
-        for i in info_lines.len()..logo.lines.len() {
-            println!(
-                "\x1b[1m{}\x1b[0m",
-                logo.render_line(&logo.lines[i], &mut current_color)
-            );
+        // Two rows of eight 3-column blocks
+        let blocks_fit = width.is_none_or(|width| blocks_indent + 24 <= width);
+        if !theme.shows("colors") || !blocks_fit {
+            return Ok(());
         }
 
-        println!();
+        writeln!(out)?;
 
-        let padding = " ".repeat(max_logo_width + 2);
+        let padding = " ".repeat(blocks_indent);
 
         // Normal colors
-        print!("{padding}");
-        print!("\x1b[40m   "); // Black
-        print!("\x1b[41m   "); // Red
-        print!("\x1b[42m   "); // Green
-        print!("\x1b[43m   "); // Yellow
-        print!("\x1b[44m   "); // Blue
-        print!("\x1b[45m   "); // Magenta
-        print!("\x1b[46m   "); // Cyan
-        print!("\x1b[47m   "); // White
-        println!("\x1b[0m");
+        write!(out, "{padding}")?;
+        write!(out, "\x1b[40m   ")?; // Black
+        write!(out, "\x1b[41m   ")?; // Red
+        write!(out, "\x1b[42m   ")?; // Green
+        write!(out, "\x1b[43m   ")?; // Yellow
+        write!(out, "\x1b[44m   ")?; // Blue
+        write!(out, "\x1b[45m   ")?; // Magenta
+        write!(out, "\x1b[46m   ")?; // Cyan
+        write!(out, "\x1b[47m   ")?; // White
+        writeln!(out, "\x1b[0m")?;
 
         // Bright colors
-        print!("{padding}");
-        print!("\x1b[100m   "); // Bright black
-        print!("\x1b[101m   "); // Bright red
-        print!("\x1b[102m   "); // Bright green
-        print!("\x1b[103m   "); // Bright yellow
-        print!("\x1b[104m   "); // Bright blue
-        print!("\x1b[105m   "); // Bright magenta
-        print!("\x1b[106m   "); // Bright cyan
-        print!("\x1b[107m   "); // Bright white
-        println!("\x1b[0m");
+        write!(out, "{padding}")?;
+        write!(out, "\x1b[100m   ")?; // Bright black
+        write!(out, "\x1b[101m   ")?; // Bright red
+        write!(out, "\x1b[102m   ")?; // Bright green
+        write!(out, "\x1b[103m   ")?; // Bright yellow
+        write!(out, "\x1b[104m   ")?; // Bright blue
+        write!(out, "\x1b[105m   ")?; // Bright magenta
+        write!(out, "\x1b[106m   ")?; // Bright cyan
+        write!(out, "\x1b[107m   ")?; // Bright white
+        writeln!(out, "\x1b[0m")?;
+
+        Ok(())
+    }
+}
+
+/// Run a `logos` subcommand and return its exit status
+fn run_logos_command(command: &LogosCommand, args: &Args) -> i32 {
+    let terminal = Terminal::detect(args.color, args.color_depth);
+    let mut out = io::stdout().lock();
+
+    let result =
+        match command {
+            LogosCommand::List(selection) => catalog::filter(selection.pattern.as_deref())
+                .and_then(|logos| {
+                    let listings = catalog::listings(logos, selection.variants);
+                    catalog::print_listings(&listings, &mut out).map_err(|e| e.to_string())
+                }),
+            LogosCommand::Show { name, small } => catalog::find(name)
+                .ok_or_else(|| format!("Logo '{name}' not found"))
+                .and_then(|file| file.load(args.get_color_overrides()))
+                .and_then(|logo| match (small, &logo.small) {
+                    (false, _) => Ok(logo),
+                    (true, Some(small)) => small.load(args.get_color_overrides()),
+                    (true, None) => Err(format!("Logo '{name}' has no small variant")),
+                })
+                .and_then(|logo| {
+                    let text: String = logo
+                        .rendered_lines()
+                        .iter()
+                        .map(|line| format!("{}\n", line.trim_end()))
+                        .collect();
+                    write!(out, "{}", terminal.adapt(&text)).map_err(|e| e.to_string())
+                }),
+            LogosCommand::Gallery(selection) => catalog::filter(selection.pattern.as_deref())
+                .and_then(|logos| {
+                    let logos: Vec<LogoFile> = catalog::listings(logos, selection.variants)
+                        .into_iter()
+                        .map(|listing| listing.logo)
+                        .collect();
+                    catalog::gallery(&logos, &terminal, &mut out).map_err(|e| e.to_string())
+                }),
+            LogosCommand::Search { term } => {
+                let listings = catalog::search(term);
+                if listings.is_empty() {
+                    Err(format!("No logo matches '{term}'"))
+                } else {
+                    catalog::print_listings(&listings, &mut out).map_err(|e| e.to_string())
+                }
+            }
+            LogosCommand::Lint { path } => return lint_logos(path),
+        };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    }
+}
+
+/// Print the problems of the logo files at `path`; the exit status is 1
+/// when any of them is an error
+fn lint_logos(path: &Path) -> i32 {
+    let linted = match lint::lint_path(path) {
+        Ok(linted) => linted,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return 1;
+        }
+    };
+
+    let (mut errors, mut warnings) = (0, 0);
+    for (file, problems) in &linted {
+        for problem in problems {
+            match problem.line {
+                Some(_) => println!("{}:{problem}", file.display()),
+                None => println!("{}: {problem}", file.display()),
+            }
+            match problem.severity {
+                lint::Severity::Error => errors += 1,
+                lint::Severity::Warning => warnings += 1,
+            }
+        }
     }
+    println!(
+        "{} files: {errors} errors, {warnings} warnings",
+        linted.len()
+    );
+
+    if errors > 0 { 1 } else { 0 }
 }
 
 fn main() {
     let args = Args::parse();
 
+    if let Some(Command::Logos { command }) = &args.command {
+        std::process::exit(run_logos_command(command, &args));
+    }
+
     if args.list_logos {
         println!("Available logos:");
         for logo in Logo::list_available() {
@@ -1577,35 +2279,163 @@ fn main() {
         return;
     }
 
-    let system_info = SystemInfo::new();
+    if let Some(builtin) = &args.import_logos {
+        let table = fs::read_to_string(builtin)
+            .map_err(|e| format!("Failed to read {}: {e}", builtin.display()))
+            .and_then(|source| upstream::convert(&source));
+        match table {
+            Ok(table) => print!("{table}"),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
-    let color_overrides = args.get_color_overrides();
+    if args.list_presets {
+        println!("Available presets:");
+        for (name, description) in Theme::list_presets() {
+            println!("  - {name:<16} {description}");
+        }
+        return;
+    }
+
+    let mut theme = args.get_theme().unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
+    if let Some(archive_path) = &args.dump_probe {
+        let mut root = SysRoot::host().recording();
+        if args.no_exec {
+            root = root.without_exec();
+        }
+        SystemInfo::new(&root, &args.get_detect_options(&theme));
+        SystemInfo::detect_os_id(&root);
 
-    let logo_result = if let Some(logo_path) = args.logo_file {
-        Logo::from_file(&logo_path, color_overrides)
+        let recorder = root.take_recording().unwrap_or_default();
+        if let Err(e) = recorder.write_archive(archive_path) {
+            eprintln!("Error: Failed to write {}: {e}", archive_path.display());
+            std::process::exit(1);
+        }
+        println!("Probe snapshot written to {}", archive_path.display());
+        return;
+    }
+
+    let root = if let Some(dir) = &args.sysroot {
+        SysRoot::fixture(dir)
+    } else if let Some(archive_path) = &args.from_probe {
+        match SysRoot::from_archive(archive_path) {
+            Ok(root) => root,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
     } else {
-        let logo_name = args
-            .logo
-            .as_ref()
-            .map(|s| s.to_lowercase())
-            .unwrap_or_else(SystemInfo::detect_os_id);
+        SysRoot::host()
+    };
+    let root = if args.no_exec {
+        root.without_exec()
+    } else {
+        root
+    };
+    let system_info = SystemInfo::new(&root, &args.get_detect_options(&theme));
 
-        Logo::load(&logo_name, color_overrides)
+    let color_overrides = args.get_color_overrides();
+    let terminal = Terminal::detect(args.color, args.color_depth);
+
+    let logo_name = args
+        .logo
+        .as_ref()
+        .map(|s| s.to_lowercase())
+        .unwrap_or_else(|| SystemInfo::detect_os_id(&root));
+    let from_text = |name: &str, content: String| {
+        Logo::from_content(
+            name,
+            &content,
+            None,
+            color_overrides.clone(),
+            args.logo_type,
+        )
+    };
+    let logo_result = match (&args.logo_file, &args.logo_inline) {
+        (Some(path), _) if path.as_os_str() == "-" => {
+            catalog::read_stdin().and_then(|content| from_text("stdin", content))
+        }
+        (Some(path), _) => Logo::from_file(path, color_overrides.clone(), args.logo_type),
+        (None, Some(text)) => {
+            catalog::inline_text(text).and_then(|content| from_text("inline", content))
+        }
+        (None, None) => catalog::choose(&logo_name, &system_info.hostname, SystemTime::now())
+            .and_then(|name| Logo::load(&name, color_overrides.clone())),
     };
 
     match logo_result {
-        Ok(logo) => system_info.display(&logo),
+        Ok(logo) => {
+            // Logos can have a small variant for narrow terminals
+            let small = logo.small.clone();
+            let small_logo = || {
+                small
+                    .as_ref()
+                    .and_then(|small| small.load(color_overrides.clone()).ok())
+            };
+            let logo = fit_logo(logo, small_logo, &mut theme, terminal.width);
+            system_info.display(&logo, &theme, &terminal);
+        }
         Err(e) => {
             eprintln!("Warning: {e}");
             eprintln!("Displaying info without logo");
 
             let empty_logo = Logo {
-                lines: vec![],
                 colors: Logo::get_default_colors(),
+                ..Logo::default()
             };
-            system_info.display(&empty_logo);
+            system_info.display(&empty_logo, &theme, &terminal);
+        }
+    }
+}
+
+/// Make the logo fit a terminal `width` columns wide: switch to its small
+/// variant, then move it on top of the information, then drop it.
+fn fit_logo(
+    logo: Logo,
+    small_logo: impl FnOnce() -> Option<Logo>,
+    theme: &mut Theme,
+    width: Option<usize>,
+) -> Logo {
+    let Some(width) = width else {
+        return logo;
+    };
+    let beside = matches!(
+        theme.logo_position,
+        LogoPosition::Left | LogoPosition::Right
+    );
+    let fits = |logo: &Logo| {
+        if beside {
+            logo.width() + theme.logo_padding + MIN_INFO_WIDTH <= width
+        } else {
+            logo.width() <= width
         }
+    };
+
+    if theme.logo_position == LogoPosition::None || fits(&logo) {
+        return logo;
+    }
+
+    let logo = match small_logo() {
+        Some(small) if small.width() < logo.width() => small,
+        _ => logo,
+    };
+    if !fits(&logo) {
+        theme.logo_position = if beside && logo.width() <= width {
+            LogoPosition::Top
+        } else {
+            LogoPosition::None
+        };
     }
+    logo
 }
 
 #[cfg(test)]
@@ -1664,27 +2494,36 @@ mod tests {
         assert_eq!(parse_color("256"), None);
     }
 
+    const FIXTURES: &[&str] = &["arch", "debian", "fedora", "nixos"];
+
+    fn fixture(name: &str) -> SysRoot {
+        SysRoot::fixture(format!("tests/fixtures/{name}"))
+    }
+
     #[test]
     fn test_no_nix_store_duplicate() {
-        let disks = SystemInfo::detect_disks();
-        let root = disks.iter().find(|(m, _, _)| m == "/");
-        let nix = disks.iter().find(|(m, _, _)| m == "/nix/store");
-
-        if let (Some(root), Some(nix)) = (root, nix) {
-            assert_ne!(
-                root.1, nix.1,
-                "/nix/store should not show if it's a bind mount of /"
-            );
-        }
+        let disks = disk::detect_disks(&fixture("nixos"), &DiskFilter::default());
+        let mounts: Vec<&str> = disks.iter().map(|d| d.mount_point.as_str()).collect();
+
+        // /nix/store is a bind mount of /, so it is not listed again
+        assert_eq!(mounts, ["/", "/boot/firmware"]);
     }
 
     #[test]
     fn test_disk_size_format() {
-        let disks = SystemInfo::detect_disks();
+        let disks: Vec<Disk> = FIXTURES
+            .iter()
+            .flat_map(|name| disk::detect_disks(&fixture(name), &DiskFilter::default()))
+            .collect();
+        assert!(!disks.is_empty());
+
+        for disk in &disks {
+            let mount = &disk.mount_point;
+            let (usage, info) = SystemInfo::format_disk(disk, SizeUnits::Iec, false);
+            let percent = &disk.usage.percent;
 
-        for (mount, info, percent) in &disks {
             assert!(
-                info.contains(" / "),
+                usage.contains(" / "),
                 "Disk {} should have 'used / total' format",
                 mount
             );
@@ -1702,7 +2541,748 @@ mod tests {
                 percent
             );
 
-            assert!(info.contains("B "), "Disk {} should have size units", mount);
+            assert!(
+                usage.contains("B / "),
+                "Disk {} should have size units",
+                mount
+            );
+        }
+
+        let fixture = Disk {
+            mount_point: "/".to_string(),
+            source: "/dev/sda2".to_string(),
+            fs_type: "ext4".to_string(),
+            subvolume: None,
+            network: false,
+            read_only: true,
+            usage: disk::DiskUsage::from_stats(&disk::FsStats {
+                fragment_size: 4096,
+                blocks: 12_800_000,
+                blocks_free: 3_200_000,
+                blocks_available: 2_560_000,
+                files: 3_276_800,
+                files_free: 2_457_600,
+            }),
+            device: None,
+        };
+
+        assert_eq!(
+            SystemInfo::format_disk(&fixture, SizeUnits::Iec, false),
+            (
+                "36.62 GiB / 48.83 GiB".to_string(),
+                " - ext4 [Read-only]".to_string()
+            )
+        );
+        assert_eq!(
+            SystemInfo::format_disk(&fixture, SizeUnits::Si, true),
+            (
+                "39.32 GB / 52.43 GB".to_string(),
+                " - ext4, 819200 / 3276800 inodes (25%) [Read-only]".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_bytes_units() {
+        assert_eq!(SystemInfo::format_bytes(512, SizeUnits::Iec), "512 B");
+        assert_eq!(SystemInfo::format_bytes(1536, SizeUnits::Iec), "1.50 KiB");
+        assert_eq!(SystemInfo::format_bytes(1500, SizeUnits::Si), "1.50 kB");
+        assert_eq!(
+            SystemInfo::format_bytes(2 * 1024 * 1024 * 1024 * 1024, SizeUnits::Iec),
+            "2.00 TiB"
+        );
+        assert_eq!(
+            SystemInfo::format_bytes(500_000_000_000, SizeUnits::Si),
+            "500.00 GB"
+        );
+    }
+
+    fn render_fixture(name: &str) -> String {
+        render_fixture_with(name, &Theme::default())
+    }
+
+    fn render_fixture_with(name: &str, theme: &Theme) -> String {
+        let root = fixture(name);
+        let info = SystemInfo::new(&root, &DetectOptions::default());
+        let logo = Logo::load(&SystemInfo::detect_os_id(&root), HashMap::new()).unwrap();
+        render_info(&info, &logo, theme, &Terminal::default())
+    }
+
+    fn render_info(info: &SystemInfo, logo: &Logo, theme: &Theme, terminal: &Terminal) -> String {
+        let mut out = Vec::new();
+        info.render(logo, theme, terminal, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_preset_layout() {
+        let theme = Theme::parse(
+            "modules = memory, os\nseparator = \" = \"\nvalue-color = white\npercent-colors = none\n",
+        )
+        .unwrap();
+        let output = render_fixture_with("arch", &theme);
+
+        let memory = output.find("Memory\x1b[0m = \x1b[97m7.81 GiB / 31.25 GiB (25%)");
+        let os = output.find("OS\x1b[0m = \x1b[97mArch Linux");
+        assert!(memory.is_some() && os.is_some(), "{output:?}");
+        assert!(memory < os);
+        assert!(!output.contains("Kernel"));
+        assert!(!output.contains("\x1b[40m"));
+    }
+
+    #[test]
+    fn test_wide_logo_alignment() {
+        assert_eq!(
+            Logo::calculate_display_width("日本$1語${c2}", LogoType::Text),
+            6
+        );
+        assert_eq!(
+            Logo::calculate_display_width("$1👨\u{200d}👩\u{200d}👧$", LogoType::Text),
+            3
+        );
+
+        let info = SystemInfo::new(&fixture("arch"), &DetectOptions::default());
+        let theme = Theme::parse("modules = os, kernel, uptime, packages, shell\n").unwrap();
+        let logo = Logo {
+            lines: vec![
+                "日本語".to_string(),
+                "$1e\u{301}e\u{301}".to_string(),
+                "👨\u{200d}👩\u{200d}👧${c1}".to_string(),
+                "ａｂｃｄ".to_string(),
+            ],
+            colors: HashMap::from([
+                ("$1".to_string(), Style::logo(Color::Red)),
+                ("$2".to_string(), Style::logo(Color::Cyan)),
+            ]),
+            ..Logo::default()
+        };
+
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+
+        let columns: Vec<usize> = output
+            .lines()
+            .filter_map(|line| Some(layout::text_width(&line[..line.find("\x1b[1;96m")?])))
+            .collect();
+        assert_eq!(columns, vec![10; 5], "{output:?}");
+    }
+
+    #[test]
+    fn test_logo_styles() {
+        let colors = HashMap::from([
+            ("$1".to_string(), Style::logo(Color::Red)),
+            ("$2".to_string(), Style::parse("normal;blue").unwrap()),
+        ]);
+        let logo = Logo {
+            lines: vec![
+                "a$2b${c1;italic}c${bold;bg=236}d$1e".to_string(),
+                "${c9}f${nonsense}g".to_string(),
+            ],
+            colors,
+            ..Logo::default()
+        };
+
+        let mut style = logo.colors.get("$1").copied();
+        assert_eq!(
+            logo.render_line(&logo.lines[0], &mut style),
+            "\x1b[1;91ma\x1b[0m\x1b[94mb\x1b[3;91mc\x1b[0m\x1b[1;91;48;5;236md\x1b[0m\x1b[1;91me"
+        );
+        // Unknown colors and invalid specs are dropped, the style carries on
+        assert_eq!(logo.render_line(&logo.lines[1], &mut style), "\x1b[1;91mfg");
+
+        // Keys follow the logo's second color, but stay bold
+        let info = SystemInfo::new(&fixture("arch"), &DetectOptions::default());
+        let theme = Theme::parse("modules = os\n").unwrap();
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+        assert!(output.contains("\x1b[1;94mOS\x1b[0m"), "{output:?}");
+
+        let theme = Theme::parse("modules = os\nkey-color = italic;fg=#ff8800\n").unwrap();
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+        assert!(
+            output.contains("\x1b[3;38;2;255;136;0mOS\x1b[0m"),
+            "{output:?}"
+        );
+    }
+
+    #[test]
+    fn test_logo_front_matter() {
+        let dir = std::env::temp_dir().join(format!("fastfetch-rs-logo-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tux.txt");
+        fs::write(
+            &path,
+            "---\ncolors = #ff8800, blue\nkey-color = green\ntitle-color = 141\nsmall = tiny\n---\n$1 /\\\n$2/__\\\n",
+        )
+        .unwrap();
+        fs::write(dir.join("tiny.txt"), "$1.\n").unwrap();
+
+        let logo = Logo::from_file(&path, HashMap::new(), LogoType::Text).unwrap();
+        let small = logo.small.clone();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(logo.lines, ["$1 /\\", "$2/__\\"]);
+        assert_eq!(logo.colors["$2"], Style::logo(Color::Blue));
+        assert_eq!(
+            small.map(|small| small.source),
+            Some(LogoSource::File(dir.join("tiny.txt")))
+        );
+
+        let info = SystemInfo::new(&fixture("arch"), &DetectOptions::default());
+        let theme = Theme::parse("modules = title, os\n").unwrap();
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+        assert!(output.contains("\x1b[1;38;2;255;136;0m /"));
+        assert!(output.contains("\x1b[1;38;5;141mbob\x1b[0m"));
+        assert!(output.contains("\x1b[1;92mOS\x1b[0m"));
+    }
+
+    #[test]
+    fn test_logo_size_limit() {
+        // Endless sources such as pipes and devices stop at the limit
+        assert_eq!(Logo::read_capped(io::repeat(b'x')).unwrap(), None);
+        assert_eq!(
+            Logo::read_capped(&b"$1x\n"[..]).unwrap(),
+            Some(b"$1x\n".to_vec())
+        );
+        assert_eq!(
+            Logo::from_file(Path::new("/dev/zero"), HashMap::new(), LogoType::Text).err(),
+            Some("Logo file too large (max: 16384 bytes)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_logo_types() {
+        let load = |content: &str, logo_type| {
+            Logo::from_content("inline", content, None, HashMap::new(), logo_type)
+        };
+        let colored = "\x1b[38;5;208m/\\\x1b[0m\n\x1b[1m/__\\\x1b[0m\n";
+
+        assert!(load(colored, LogoType::Text).is_err());
+        let logo = load(colored, LogoType::Ansi).unwrap();
+        assert_eq!(logo.lines.len(), 2);
+        assert_eq!(logo.width(), 4);
+        assert_eq!(
+            logo.rendered_lines()[0],
+            "\x1b[38;5;208m/\\\x1b[0m\x1b[0m  "
+        );
+
+        // Anything but colors is dropped, and colors carry over to the next line
+        let logo = load(
+            "\x1b[2J\x1b[H\x1b]0;pwned\x07\x1b[31m\x1b[?25l##\r\n##\x1b[0m\n\x1b]8;;https://example.org\x1b\\##\n",
+            LogoType::Ansi,
+        )
+        .unwrap();
+        assert_eq!(logo.lines, ["\x1b[31m##", "\x1b[31m##\x1b[0m", "##"]);
+        assert_eq!(logo.width(), 2);
+        assert_eq!(
+            Logo::calculate_display_width(
+                "$1\x1b[1;38;2;255;136;0m★\x1b[0m${c2}x\x1b[m",
+                LogoType::Text
+            ),
+            2
+        );
+
+        // Pre-colored art has no placeholders
+        let logo = load("\x1b[31mPrice $5 ${c1}\x1b[0m\n", LogoType::Ansi).unwrap();
+        assert_eq!(logo.width(), 14);
+        assert_eq!(
+            logo.rendered_lines()[0],
+            "\x1b[31mPrice $5 ${c1}\x1b[0m\x1b[0m"
+        );
+
+        let inline = catalog::inline_text(r"$1 /\\\n$2/__\\").unwrap();
+        let logo = load(&inline, LogoType::Text).unwrap();
+        assert_eq!(logo.lines, ["$1 /\\", "$2/__\\"]);
+    }
+
+    #[test]
+    fn test_hidden_modules_keep_logo_lines() {
+        let root = fixture("debian");
+        let mut info = SystemInfo::new(&root, &DetectOptions::default());
+        let logo = Logo::load(&SystemInfo::detect_os_id(&root), HashMap::new()).unwrap();
+        info.host = None;
+        info.kernel = None;
+        info.gpu = None;
+        info.shell = Some("Unknown Shell 1.0".to_string());
+
+        let output = render_info(&info, &logo, &Theme::default(), &Terminal::default());
+        let lines: Vec<String> = output.lines().map(layout::visible_text).collect();
+
+        // Every logo line is still there, in order, one per row
+        let mut color = None;
+        for (line, logo_line) in lines.iter().zip(&logo.lines) {
+            let logo_line = layout::visible_text(&logo.render_line(logo_line, &mut color));
+            assert!(line.starts_with(logo_line.trim_end()), "{line:?}");
+        }
+        assert!(!output.contains("Host") && !output.contains("Kernel"));
+        assert!(!output.contains("GPU"));
+        assert!(output.contains("Shell\x1b[0m: Unknown Shell 1.0"));
+
+        // Info lines follow each other without gaps
+        let chassis = lines.iter().position(|l| l.contains("Chassis: ")).unwrap();
+        assert!(
+            lines[chassis + 1].contains("Uptime: "),
+            "{:?}",
+            lines[chassis + 1]
+        );
+    }
+
+    #[test]
+    fn test_info_lines_past_logo_reset_colors() {
+        let info = SystemInfo::new(&fixture("arch"), &DetectOptions::default());
+        let logo = Logo {
+            lines: vec!["$1x".to_string()],
+            colors: Logo::get_default_colors(),
+            ..Logo::default()
+        };
+        let theme =
+            Theme::parse("modules = title, os, kernel, uptime\nvalue-color = red\n").unwrap();
+
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+        assert!(output.lines().count() > 2);
+        for line in output.lines() {
+            assert!(line.ends_with("\x1b[0m"), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let root = fixture("debian");
+        let info = SystemInfo::new(&root, &DetectOptions::default());
+        let logo = Logo::load(&SystemInfo::detect_os_id(&root), HashMap::new()).unwrap();
+        let terminal = Terminal {
+            color: false,
+            ..Terminal::default()
+        };
+
+        let output = render_info(&info, &logo, &Theme::default(), &terminal);
+
+        assert!(!output.contains('\x1b'), "{output:?}");
+        assert!(output.contains("  OS: Debian GNU/Linux 12"));
+        assert!(output.contains("Memory: ") && output.contains("%)"));
+        assert!(output.starts_with("        _,met$$$$$$$$$$gg."));
+        // The color blocks would be blank
+        assert!(
+            output
+                .lines()
+                .all(|line| line.is_empty() || !line.trim().is_empty())
+        );
+    }
+
+    #[test]
+    fn test_logo_positions() {
+        let root = fixture("debian");
+        let info = SystemInfo::new(&root, &DetectOptions::default());
+        let logo = Logo::load(&SystemInfo::detect_os_id(&root), HashMap::new()).unwrap();
+        let render = |settings: &str, width: Option<usize>| {
+            let theme = Theme::parse(settings).unwrap();
+            let terminal = Terminal {
+                width,
+                ..Terminal::default()
+            };
+            render_info(&info, &logo, &theme, &terminal)
+                .lines()
+                .map(layout::visible_text)
+                .collect::<Vec<_>>()
+        };
+
+        let lines = render("logo-position = right\nlogo-padding = 3\n", None);
+        assert!(lines[2].starts_with("OS: Debian GNU/Linux 12"));
+        assert!(lines[2].ends_with("   ,g$$$$P\"\"       \"\"\"Y$$$$.\"."));
+        assert!(lines[0].ends_with("_,met$$$$$$$$$$gg."));
+
+        let lines = render("logo-position = top\n", None);
+        assert_eq!(lines[0], "        _,met$$$$$$$$$$gg.");
+        let title = lines
+            .iter()
+            .position(|l| l == "alice@bookworm-desk")
+            .unwrap();
+        assert_eq!(title, logo.lines.len() + 1);
+        assert!(lines[title - 1].is_empty());
+
+        let lines = render("logo-position = none\n", None);
+        assert_eq!(lines[0], "alice@bookworm-desk");
+        assert!(!lines.iter().any(|l| l.contains("$$$")));
+
+        // Long values are cut to the terminal width
+        let lines = render("", Some(60));
+        assert!(
+            lines.iter().all(|l| layout::text_width(l) <= 60),
+            "{lines:#?}"
+        );
+        assert!(lines.iter().any(|l| l.ends_with('…')));
+    }
+
+    #[test]
+    fn test_fit_logo() {
+        let logo = |width: usize| Logo {
+            lines: vec!["x".repeat(width)],
+            colors: HashMap::new(),
+            ..Logo::default()
+        };
+        let fit = |width: Option<usize>, small: Option<usize>| {
+            let mut theme = Theme::default();
+            let fitted = fit_logo(logo(30), || small.map(logo), &mut theme, width);
+            (fitted.width(), theme.logo_position)
+        };
+
+        assert_eq!(fit(None, Some(10)), (30, LogoPosition::Left));
+        assert_eq!(fit(Some(100), Some(10)), (30, LogoPosition::Left));
+        assert_eq!(fit(Some(60), Some(10)), (10, LogoPosition::Left));
+        assert_eq!(fit(Some(45), Some(10)), (10, LogoPosition::Top));
+        assert_eq!(fit(Some(45), None), (30, LogoPosition::Top));
+        assert_eq!(fit(Some(20), None), (30, LogoPosition::None));
+    }
+
+    #[test]
+    fn test_percent_bars() {
+        let theme = |settings: &str| {
+            Theme::parse(&format!(
+                "modules = memory, battery\npercent-colors = none\n{settings}"
+            ))
+            .unwrap()
+        };
+
+        let output = render_fixture_with("debian", &theme("percent-style = bar\n"));
+        assert!(output.contains("Memory\x1b[0m: [■■■□□□□□□□] 4.55 GiB / 15.55 GiB\x1b[0m"));
+        assert!(output.contains("5B10W13930)\x1b[0m: [■■■■■■■■■□] [Charging]"));
+
+        let output = render_fixture_with(
+            "debian",
+            &theme("percent-style = both\nbar-width = 4\nbar-chars = #-\nbar-border = none\n"),
+        );
+        assert!(output.contains("Memory\x1b[0m: #--- 4.55 GiB / 15.55 GiB (29%)"));
+        assert!(output.contains("5B10W13930)\x1b[0m: ###- 87% [Charging]"));
+
+        // Full batteries are good news, full disks are not
+        let output = render_fixture_with("debian", &Theme::default());
+        assert!(output.contains("\x1b[92m87%"));
+    }
+
+    #[test]
+    fn test_disk_inode_percent_bars() {
+        let options = DetectOptions {
+            disk_inodes: true,
+            ..DetectOptions::default()
+        };
+        let mut info = SystemInfo::new(&fixture("arch"), &options);
+        // The inode percentage ends with the disk one
+        info.disks = vec![Disk {
+            mount_point: "/".to_string(),
+            source: "/dev/sda2".to_string(),
+            fs_type: "ext4".to_string(),
+            subvolume: None,
+            network: false,
+            read_only: false,
+            usage: disk::DiskUsage::from_stats(&disk::FsStats {
+                fragment_size: 1024 * 1024,
+                blocks: 100,
+                blocks_free: 96,
+                blocks_available: 96,
+                files: 100,
+                files_free: 86,
+            }),
+            device: None,
+        }];
+        let logo = Logo::default();
+        let render = |settings: &str| {
+            let theme = Theme::parse(&format!(
+                "modules = disk\npercent-colors = none\n{settings}"
+            ))
+            .unwrap();
+            render_info(&info, &logo, &theme, &Terminal::default())
+        };
+
+        let output = render("percent-style = bar\nbar-chars = #-\n");
+        assert!(
+            output.contains(
+                "Disk (/)\x1b[0m: [----------] 4.00 MiB / 100.00 MiB - ext4, 14 / 100 inodes (14%)\x1b[0m"
+            ),
+            "{output:?}"
+        );
+
+        let output = render("percent-style = percent\n");
+        assert!(
+            output.contains("4.00 MiB / 100.00 MiB (4%) - ext4, 14 / 100 inodes (14%)\x1b[0m"),
+            "{output:?}"
+        );
+    }
+
+    #[test]
+    fn test_battery_and_cpu_usage() {
+        let root = fixture("debian");
+        let batteries = battery::detect_batteries(&root);
+        assert_eq!(batteries.len(), 1);
+        assert_eq!(batteries[0].name, "BAT0");
+        assert_eq!(batteries[0].capacity, 87);
+        assert_eq!(batteries[0].status.as_deref(), Some("Charging"));
+        assert!(battery::detect_batteries(&fixture("arch")).is_empty());
+
+        // A snapshot never changes, so this is the average since boot
+        assert_eq!(
+            SystemInfo::detect_cpu_usage(&root, Duration::ZERO),
+            Some(26)
+        );
+        assert_eq!(
+            SystemInfo::detect_cpu_usage(&fixture("arch"), Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_title_and_key_styling() {
+        let mut info = SystemInfo::new(&fixture("arch"), &DetectOptions::default());
+        info.username = "ユーザー".to_string();
+        let theme = Theme::parse(
+            "modules = title, underline, os, cpu\ntitle-format = {user} on {host}\nunderline-char = =\nkey-width = 5\n",
+        )
+        .unwrap();
+
+        let logo = Logo {
+            lines: vec![],
+            colors: HashMap::new(),
+            ..Logo::default()
+        };
+        let output = render_info(&info, &logo, &theme, &Terminal::default());
+
+        // 8 columns for the name, 11 for " on archbox"
+        assert!(output.contains(&format!("\x1b[96m{}\x1b[0m", "=".repeat(19))));
+        assert!(output.contains("\x1b[1;96m on \x1b[0m"));
+        assert!(output.contains("OS\x1b[0m   : Arch"));
+        assert!(output.contains("CPU\x1b[0m  : AMD"));
+    }
+
+    #[test]
+    fn test_golden_output() {
+        for name in FIXTURES {
+            let actual = render_fixture(name);
+            let golden = format!("tests/golden/{name}.txt");
+
+            if std::env::var_os("UPDATE_GOLDEN").is_some() {
+                fs::write(&golden, &actual).unwrap();
+                continue;
+            }
+
+            let expected = fs::read_to_string(&golden).unwrap_or_default();
+            assert_eq!(
+                actual, expected,
+                "output for {name} differs from {golden}, rerun with UPDATE_GOLDEN=1 to accept"
+            );
+        }
+    }
+
+    #[test]
+    fn test_probe_round_trip() {
+        for name in FIXTURES {
+            let recording = fixture(name).recording();
+            SystemInfo::new(&recording, &DetectOptions::default());
+            SystemInfo::detect_os_id(&recording);
+
+            let archive = std::env::temp_dir().join(format!(
+                "fastfetch-rs-probe-{name}-{}.tar",
+                std::process::id()
+            ));
+            recording
+                .take_recording()
+                .unwrap()
+                .write_archive(&archive)
+                .unwrap();
+            let replay = SysRoot::from_archive(&archive).unwrap();
+            fs::remove_file(&archive).unwrap();
+
+            let options = DetectOptions::default();
+            let replayed = SystemInfo::new(&replay, &options);
+            let mut original = SystemInfo::new(&fixture(name), &options);
+            assert_eq!(replayed.hostname, "redacted-host");
+            assert!(
+                !replayed
+                    .local_ip
+                    .as_ref()
+                    .is_some_and(|(ip, _)| ip.starts_with("10.0.0."))
+            );
+            original.hostname = replayed.hostname.clone();
+            original.local_ip = replayed.local_ip.clone();
+
+            let logo = Logo::load(&SystemInfo::detect_os_id(&replay), HashMap::new()).unwrap();
+            let (theme, terminal) = (Theme::default(), Terminal::default());
+            assert_eq!(
+                render_info(&replayed, &logo, &theme, &terminal),
+                render_info(&original, &logo, &theme, &terminal),
+                "replay of {name} differs"
+            );
         }
     }
+
+    #[test]
+    fn test_memory_details() {
+        let fedora = fixture("fedora");
+        let details = [
+            MemoryDetail::Buffers,
+            MemoryDetail::Zram,
+            MemoryDetail::Zswap,
+            MemoryDetail::Hugepages,
+        ];
+        let (memory, percent, breakdown) =
+            SystemInfo::detect_memory(&fedora, SizeUnits::Iec, &details).unwrap();
+        assert_eq!(memory, "1.65 GiB / 7.65 GiB");
+        assert_eq!(percent, 21);
+        assert_eq!(
+            breakdown,
+            " [Buffers 200.00 MiB, zram0 136.00 MiB (512.00 MiB stored, 4.00x), HugePages 12.00 MiB / 16.00 MiB]"
+        );
+
+        assert_eq!(
+            SystemInfo::detect_physical_memory(&fixture("arch"), SizeUnits::Iec),
+            vec!["2 x 16.00 GiB DDR4 @ 3200 MT/s (Kingston)"]
+        );
+        assert!(SystemInfo::detect_physical_memory(&fedora, SizeUnits::Iec).is_empty());
+    }
+
+    #[test]
+    fn test_swap_devices() {
+        let fedora = swap::detect_swaps(&fixture("fedora")).unwrap();
+        assert_eq!(fedora.len(), 1);
+        assert_eq!(
+            SystemInfo::format_swap(&fedora[0], SizeUnits::Iec),
+            (
+                "512.00 MiB / 4.00 GiB".to_string(),
+                " - zram (zstd, 4.00x), priority 100".to_string()
+            )
+        );
+
+        // Without /proc/swaps, the meminfo total stands in
+        let nixos = swap::detect_swaps(&fixture("nixos")).unwrap();
+        assert_eq!(nixos.len(), 1);
+        assert_eq!(nixos[0].path, "");
+        assert_eq!(nixos[0].percent(), 50);
+
+        let zswap = swap::detect_zswap(&fixture("nixos")).unwrap();
+        assert!(zswap.enabled);
+        assert_eq!(zswap.max_pool_percent, Some(20));
+        assert_eq!(swap::detect_zswap(&fixture("arch")), None);
+    }
+
+    #[test]
+    fn test_dmi_modules() {
+        let arch = fixture("arch");
+        let dmi = Dmi::detect(&arch);
+        assert_eq!(dmi.product.family, None);
+        assert_eq!(
+            SystemInfo::detect_host(&arch, &dmi).as_deref(),
+            Some("Micro-Star International Co., Ltd. MS-7C56 (1.0)")
+        );
+        assert_eq!(
+            SystemInfo::format_bios(&dmi).as_deref(),
+            Some("American Megatrends International, LLC. A.J0 (03/01/2024) [UEFI]")
+        );
+        assert_eq!(SystemInfo::format_chassis(&dmi).as_deref(), Some("Desktop"));
+
+        let debian = fixture("debian");
+        let dmi = Dmi::detect(&debian);
+        assert_eq!(
+            SystemInfo::detect_host(&debian, &dmi).as_deref(),
+            Some(
+                "LENOVO 20KHCTO1WW (ThinkPad X1 Carbon 6th, SKU LENOVO_MT_20KH_BU_Think_FM_ThinkPad X1 Carbon 6th)"
+            )
+        );
+        assert_eq!(
+            SystemInfo::format_chassis(&dmi).as_deref(),
+            Some("Notebook")
+        );
+
+        let fedora = fixture("fedora");
+        let dmi = Dmi::detect(&fedora);
+        assert!(SystemInfo::format_bios(&dmi).unwrap().ends_with("[Legacy]"));
+        assert_eq!(SystemInfo::format_board(&dmi), None);
+
+        let nixos = fixture("nixos");
+        let dmi = Dmi::detect(&nixos);
+        assert_eq!(dmi, Dmi::default());
+        assert_eq!(
+            SystemInfo::detect_host(&nixos, &dmi).as_deref(),
+            Some("Raspberry Pi 4 Model B Rev 1.4")
+        );
+        assert_eq!(SystemInfo::format_bios(&dmi), None);
+    }
+
+    #[test]
+    fn test_shell_from_parent_process() {
+        // $SHELL is zsh, but fastfetch-rs was started from fish
+        assert_eq!(
+            SystemInfo::detect_shell(&fixture("arch")).as_deref(),
+            Some("fish 3.7.1")
+        );
+        assert_eq!(
+            SystemInfo::detect_shell(&fixture("arch").without_exec()).as_deref(),
+            Some("fish")
+        );
+
+        // The parent is sudo, started from bash
+        let fedora = shell::detect_shell(&fixture("fedora")).unwrap();
+        assert_eq!(fedora.path.as_deref(), Some("/usr/bin/bash"));
+        assert_eq!(fedora.version.as_deref(), Some("5.2.26"));
+
+        // Login shell known only by its comm, run through the $SHELL path
+        assert_eq!(
+            SystemInfo::detect_shell(&fixture("nixos")).as_deref(),
+            Some("bash 5.2.26")
+        );
+    }
+
+    #[test]
+    fn test_no_exec() {
+        let debian = fixture("debian").without_exec();
+        assert_eq!(
+            SystemInfo::detect_packages(&debian).as_deref(),
+            Some("8 (dpkg), 2 (flatpak)")
+        );
+        assert_eq!(SystemInfo::detect_shell(&debian).as_deref(), Some("bash"));
+        assert_eq!(SystemInfo::detect_gpu(&debian), None);
+        assert_eq!(SystemInfo::detect_display(&debian), None);
+        assert_eq!(
+            SystemInfo::detect_packages(&fixture("fedora").without_exec()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fixture_detection() {
+        let debian = fixture("debian");
+        assert_eq!(SystemInfo::detect_os_id(&debian), "debian");
+        assert_eq!(
+            SystemInfo::detect_display(&debian).as_deref(),
+            Some("1920x1080 @ 60 Hz in 14\"")
+        );
+        assert_eq!(SystemInfo::detect_display(&fixture("arch")), None);
+        assert_eq!(
+            SystemInfo::detect_packages(&debian).as_deref(),
+            Some("8 (dpkg), 2 (flatpak)")
+        );
+        assert_eq!(
+            SystemInfo::detect_uptime(&debian).as_deref(),
+            Some("1 day, 2 hours, 3 mins")
+        );
+        assert_eq!(
+            SystemInfo::detect_shell(&debian).as_deref(),
+            Some("bash 5.2.15")
+        );
+
+        let arch = fixture("arch");
+        assert_eq!(
+            SystemInfo::detect_packages(&arch).as_deref(),
+            Some("5 (pacman)")
+        );
+        let arch_mounts: Vec<String> = disk::detect_disks(&arch, &DiskFilter::default())
+            .into_iter()
+            .map(|d| d.mount_point)
+            .collect();
+        assert_eq!(arch_mounts, vec!["/", "/home", "/mnt/nas"]);
+
+        assert_eq!(
+            SystemInfo::detect_packages(&fixture("fedora")).as_deref(),
+            Some("4 (rpm)")
+        );
+        assert_eq!(
+            SystemInfo::detect_packages(&fixture("nixos")).as_deref(),
+            Some("4 (nix-system)")
+        );
+    }
 }
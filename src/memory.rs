@@ -0,0 +1,336 @@
+use crate::smbios::{self, Structure};
+use crate::sysroot::SysRoot;
+use std::collections::HashMap;
+
+/// SMBIOS "Memory Device" structure, one per DIMM slot.
+const SMBIOS_MEMORY_DEVICE: u8 = 17;
+
+/// Optional parts of the memory breakdown, selected with `--memory-details`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum MemoryDetail {
+    Buffers,
+    Cache,
+    Shared,
+    Zram,
+    Zswap,
+    Hugepages,
+}
+
+/// The parts of `/proc/meminfo` we report, in bytes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemInfo {
+    pub total: u64,
+    pub available: u64,
+    pub free: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    pub zswap: u64,
+    pub zswapped: u64,
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    pub hugepage_size: u64,
+}
+
+impl MemInfo {
+    pub fn parse(meminfo: &str) -> Option<Self> {
+        let values: HashMap<&str, u64> = meminfo
+            .lines()
+            .filter_map(|line| {
+                let (key, rest) = line.split_once(':')?;
+                let mut fields = rest.split_whitespace();
+                let value: u64 = fields.next()?.parse().ok()?;
+                // Everything is in kB except the HugePages_* page counts
+                let scale = if fields.next() == Some("kB") { 1024 } else { 1 };
+                Some((key, value * scale))
+            })
+            .collect();
+        let get = |key: &str| values.get(key).copied().unwrap_or(0);
+
+        let total = get("MemTotal");
+        if total == 0 {
+            return None;
+        }
+
+        let free = get("MemFree");
+        let buffers = get("Buffers");
+        // Like `free`, count reclaimable slab as cache
+        let cached = get("Cached") + get("SReclaimable");
+        let shared = get("Shmem");
+
+        // Kernels before 3.14 lack MemAvailable: estimate it the way they
+        // would, from free memory and the page cache that can be dropped
+        let available = values
+            .get("MemAvailable")
+            .copied()
+            .unwrap_or_else(|| (free + buffers + cached).saturating_sub(shared))
+            .min(total);
+
+        Some(MemInfo {
+            total,
+            available,
+            free,
+            buffers,
+            cached,
+            shared,
+            swap_total: get("SwapTotal"),
+            swap_free: get("SwapFree"),
+            zswap: get("Zswap"),
+            zswapped: get("Zswapped"),
+            hugepages_total: get("HugePages_Total"),
+            hugepages_free: get("HugePages_Free"),
+            hugepage_size: get("Hugepagesize"),
+        })
+    }
+
+    pub fn used(&self) -> u64 {
+        self.total - self.available
+    }
+
+    pub fn percent(&self) -> u32 {
+        (self.used() as f64 / self.total as f64 * 100.0) as u32
+    }
+}
+
+pub fn read_meminfo(root: &SysRoot) -> Option<MemInfo> {
+    MemInfo::parse(&root.read_to_string("/proc/meminfo").ok()?)
+}
+
+/// A compressed RAM block device
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZramDevice {
+    pub name: String,
+    pub algorithm: Option<String>,
+    /// Uncompressed size of the data stored
+    pub original_size: u64,
+    /// Size of that data once compressed
+    pub compressed_size: u64,
+    /// RAM actually taken, including allocator overhead
+    pub memory_used: u64,
+}
+
+impl ZramDevice {
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.compressed_size > 0).then(|| self.original_size as f64 / self.compressed_size as f64)
+    }
+}
+
+pub fn detect_zram(root: &SysRoot) -> Vec<ZramDevice> {
+    let Ok(entries) = root.read_dir("/sys/block") else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter(|entry| entry.name.starts_with("zram"))
+        .filter_map(|entry| {
+            let base = format!("/sys/block/{}", entry.name);
+            let mm_stat = root.read_to_string(&format!("{base}/mm_stat")).ok()?;
+            let stats: Vec<u64> = mm_stat
+                .split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            // The currently selected algorithm is shown in brackets
+            let algorithm = root
+                .read_to_string(&format!("{base}/comp_algorithm"))
+                .ok()
+                .and_then(|algorithms| {
+                    let start = algorithms.find('[')? + 1;
+                    let end = algorithms[start..].find(']')? + start;
+                    Some(algorithms[start..end].to_string())
+                });
+
+            Some(ZramDevice {
+                name: entry.name.clone(),
+                algorithm,
+                original_size: *stats.first()?,
+                compressed_size: *stats.get(1)?,
+                memory_used: *stats.get(2)?,
+            })
+        })
+        .collect()
+}
+
+/// An installed memory module, from SMBIOS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dimm {
+    pub locator: Option<String>,
+    pub size: u64,
+    pub memory_type: Option<&'static str>,
+    /// Rated speed in MT/s
+    pub speed: Option<u32>,
+    pub manufacturer: Option<String>,
+    pub part_number: Option<String>,
+}
+
+impl Dimm {
+    /// `None` for empty slots and entries without a usable size
+    fn from_structure(s: &Structure) -> Option<Self> {
+        let size = match s.word(0x0C)? {
+            0 | 0xFFFF => return None,
+            0x7FFF => s.dword(0x1C)? as u64 * 1024 * 1024,
+            size if size & 0x8000 != 0 => (size & 0x7FFF) as u64 * 1024,
+            size => size as u64 * 1024 * 1024,
+        };
+
+        let speed = match s.word(0x15) {
+            Some(0) | None => None,
+            Some(0xFFFF) => s.dword(0x54).filter(|&speed| speed > 0),
+            Some(speed) => Some(speed as u32),
+        };
+
+        Some(Dimm {
+            locator: s.string(0x10).map(str::to_string),
+            size,
+            memory_type: s.byte(0x12).and_then(memory_type_name),
+            speed,
+            manufacturer: s
+                .string(0x17)
+                .filter(|m| !is_placeholder(m))
+                .map(str::to_string),
+            part_number: s
+                .string(0x1A)
+                .filter(|p| !is_placeholder(p))
+                .map(str::to_string),
+        })
+    }
+}
+
+fn memory_type_name(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x03 => "DRAM",
+        0x07 => "RAM",
+        0x0F => "SDRAM",
+        0x12 => "DDR",
+        0x13 => "DDR2",
+        0x14 => "DDR2 FB-DIMM",
+        0x18 => "DDR3",
+        0x1A => "DDR4",
+        0x1B => "LPDDR",
+        0x1C => "LPDDR2",
+        0x1D => "LPDDR3",
+        0x1E => "LPDDR4",
+        0x20 => "HBM",
+        0x21 => "HBM2",
+        0x22 => "DDR5",
+        0x23 => "LPDDR5",
+        0x24 => "HBM3",
+        _ => return None,
+    })
+}
+
+fn is_placeholder(value: &str) -> bool {
+    matches!(
+        value.to_ascii_lowercase().as_str(),
+        "unknown" | "not specified" | "undefined" | "0000" | "00000000"
+    )
+}
+
+pub fn detect_dimms(root: &SysRoot) -> Vec<Dimm> {
+    smbios::read_structures(root, SMBIOS_MEMORY_DEVICE)
+        .iter()
+        .filter_map(Dimm::from_structure)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smbios::parse_table;
+    use crate::smbios::tests::structure;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "\
+MemTotal:       16384000 kB
+MemFree:         2048000 kB
+MemAvailable:    8192000 kB
+Buffers:          512000 kB
+Cached:          4096000 kB
+Shmem:            256000 kB
+SReclaimable:     128000 kB
+Zswap:             10240 kB
+Zswapped:          40960 kB
+HugePages_Total:       4
+HugePages_Free:        1
+Hugepagesize:       2048 kB
+";
+        let info = MemInfo::parse(meminfo).unwrap();
+        assert_eq!(info.used(), 8192000 * 1024);
+        assert_eq!(info.percent(), 50);
+        assert_eq!(info.cached, 4224000 * 1024);
+        assert_eq!(info.shared, 256000 * 1024);
+        assert_eq!(info.zswapped, 40960 * 1024);
+        assert_eq!(info.hugepages_total, 4);
+        assert_eq!(info.hugepages_free, 1);
+        assert_eq!(info.hugepage_size, 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_meminfo_without_memavailable() {
+        let meminfo = "\
+MemTotal:        1024000 kB
+MemFree:          100000 kB
+Buffers:           50000 kB
+Cached:           300000 kB
+Shmem:             50000 kB
+";
+        let info = MemInfo::parse(meminfo).unwrap();
+        assert_eq!(info.available, 400000 * 1024);
+        assert_eq!(info.percent(), 60);
+
+        assert_eq!(MemInfo::parse("MemFree: 100 kB\n"), None);
+    }
+
+    fn memory_device(size: u16, mem_type: u8, speed: u16, extended_size: u32) -> Vec<u8> {
+        // Everything after the 4 byte header, up to the extended speed field
+        let mut formatted = vec![0u8; 0x54];
+        let mut set = |offset: usize, bytes: &[u8]| {
+            formatted[offset - 4..offset - 4 + bytes.len()].copy_from_slice(bytes);
+        };
+        set(0x0C, &size.to_le_bytes());
+        set(0x10, &[1]);
+        set(0x12, &[mem_type]);
+        set(0x15, &speed.to_le_bytes());
+        set(0x17, &[2]);
+        set(0x1A, &[3]);
+        set(0x1C, &extended_size.to_le_bytes());
+        structure(
+            SMBIOS_MEMORY_DEVICE,
+            &formatted,
+            &["DIMM_A1", "Kingston", "KF432C16BB/16"],
+        )
+    }
+
+    #[test]
+    fn test_parse_memory_devices() {
+        let mut table = memory_device(16384, 0x1A, 3200, 0);
+        table.extend(memory_device(0, 0x02, 0, 0));
+        table.extend(memory_device(0x7FFF, 0x22, 4800, 65536));
+        table.extend(memory_device(0x8000 | 512, 0x12, 0, 0));
+
+        let dimms: Vec<Dimm> = parse_table(&table)
+            .iter()
+            .filter_map(Dimm::from_structure)
+            .collect();
+
+        assert_eq!(dimms.len(), 3);
+        assert_eq!(
+            dimms[0],
+            Dimm {
+                locator: Some("DIMM_A1".to_string()),
+                size: 16 * 1024 * 1024 * 1024,
+                memory_type: Some("DDR4"),
+                speed: Some(3200),
+                manufacturer: Some("Kingston".to_string()),
+                part_number: Some("KF432C16BB/16".to_string()),
+            }
+        );
+        assert_eq!(dimms[1].size, 64 * 1024 * 1024 * 1024);
+        assert_eq!(dimms[1].memory_type, Some("DDR5"));
+        assert_eq!(dimms[2].size, 512 * 1024);
+        assert_eq!(dimms[2].speed, None);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::terminal::ColorDepth;
+
+/// The 16 base colors as xterm draws them
+const BASE_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels of the 6x6x6 color cube (indices 16-231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// RGB value of a 256-color palette index
+pub fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASE_COLORS[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Nearest 256-color index, from the color cube or the grey ramp
+pub fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let nearest_level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap() as u8
+    };
+    let (r, g, b) = rgb;
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance(rgb, ansi256_to_rgb(grey)) < distance(rgb, ansi256_to_rgb(cube)) {
+        grey
+    } else {
+        cube
+    }
+}
+
+/// Nearest of the 16 base colors
+pub fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> u8 {
+    (0..BASE_COLORS.len())
+        .min_by_key(|&i| distance(rgb, BASE_COLORS[i]))
+        .unwrap() as u8
+}
+
+/// SGR parameters for a base color; `background` picks 40-47/100-107
+fn ansi16_params(index: u8, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    if index < 8 {
+        (base + index as u32).to_string()
+    } else {
+        (base + 60 + (index - 8) as u32).to_string()
+    }
+}
+
+/// `text` with 24-bit and 256-color SGR sequences rewritten to the nearest
+/// colors `depth` can show
+pub fn downsample(text: &str, depth: ColorDepth) -> String {
+    if depth == ColorDepth::TrueColor {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("\x1b[") {
+        result.push_str(&rest[..start]);
+        let sequence = &rest[start + 2..];
+        let end = sequence.find(|c: char| ('@'..='~').contains(&c));
+
+        match end {
+            Some(end) if sequence[end..].starts_with('m') => {
+                result.push_str("\x1b[");
+                result.push_str(&downsample_params(&sequence[..end], depth));
+                result.push('m');
+                rest = &sequence[end + 1..];
+            }
+            Some(end) => {
+                result.push_str(&rest[start..start + 2 + end + 1]);
+                rest = &sequence[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parameters of one SGR sequence, with extended colors downsampled
+fn downsample_params(params: &str, depth: ColorDepth) -> String {
+    let params: Vec<&str> = params.split(';').collect();
+    let mut result: Vec<String> = Vec::with_capacity(params.len());
+    let number = |i: usize| params.get(i).and_then(|p| p.parse::<u8>().ok());
+
+    let mut i = 0;
+    while i < params.len() {
+        let background = params[i] == "48";
+        if params[i] == "38" || background {
+            match (params.get(i + 1).copied(), depth) {
+                (Some("2"), _) => {
+                    if let (Some(r), Some(g), Some(b)) =
+                        (number(i + 2), number(i + 3), number(i + 4))
+                    {
+                        result.push(match depth {
+                            ColorDepth::Ansi16 => {
+                                ansi16_params(rgb_to_ansi16((r, g, b)), background)
+                            }
+                            _ => format!("{};5;{}", params[i], rgb_to_ansi256((r, g, b))),
+                        });
+                        i += 5;
+                        continue;
+                    }
+                }
+                (Some("5"), ColorDepth::Ansi16) => {
+                    if let Some(index) = number(i + 2) {
+                        let index = if index < 16 {
+                            index
+                        } else {
+                            rgb_to_ansi16(ansi256_to_rgb(index))
+                        };
+                        result.push(ansi16_params(index, background));
+                        i += 3;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+        }
+        result.push(params[i].to_string());
+        i += 1;
+    }
+
+    result.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_colors() {
+        assert_eq!(rgb_to_ansi256((0xff, 0x88, 0x00)), 208);
+        assert_eq!(rgb_to_ansi256((0x17, 0x93, 0xd1)), 32);
+        assert_eq!(rgb_to_ansi256((0x80, 0x80, 0x80)), 244);
+        assert_eq!(rgb_to_ansi256((0, 0, 0)), 16);
+        assert_eq!(ansi256_to_rgb(208), (255, 135, 0));
+        assert_eq!(ansi256_to_rgb(244), (128, 128, 128));
+
+        assert_eq!(rgb_to_ansi16((0xff, 0x10, 0x10)), 9);
+        assert_eq!(rgb_to_ansi16((0x17, 0x93, 0xd1)), 6);
+        assert_eq!(rgb_to_ansi16((0xf0, 0xf0, 0xf0)), 7);
+        assert_eq!(rgb_to_ansi16((0xfa, 0xfa, 0xfa)), 15);
+    }
+
+    #[test]
+    fn test_downsample() {
+        let text = "\x1b[1;38;2;255;136;0mArch\x1b[0m \x1b[48;5;236m \x1b[2K";
+        assert_eq!(downsample(text, ColorDepth::TrueColor), text);
+        assert_eq!(
+            downsample(text, ColorDepth::Ansi256),
+            "\x1b[1;38;5;208mArch\x1b[0m \x1b[48;5;236m \x1b[2K"
+        );
+        assert_eq!(
+            downsample(text, ColorDepth::Ansi16),
+            "\x1b[1;33mArch\x1b[0m \x1b[40m \x1b[2K"
+        );
+        // Base colors and malformed sequences are left alone
+        assert_eq!(
+            downsample("\x1b[96m\x1b[38;2;1m\x1b[", ColorDepth::Ansi16),
+            "\x1b[96m\x1b[38;2;1m\x1b["
+        );
+    }
+}
@@ -0,0 +1,485 @@
+use crate::sysroot::{DirEntry, PROBE_DIR};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Replaces the machine's hostname in a dump.
+const REDACTED_HOSTNAME: &str = "redacted-host";
+
+/// Upper bound on the uncompressed size of a replayed archive.
+const MAX_ARCHIVE_SIZE: u64 = 64 * 1024 * 1024;
+
+static IPV4_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}\b").unwrap());
+
+static IPV6_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(inet6\s+)([0-9a-fA-F:]+)").unwrap());
+
+static MAC_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(link/\w+\s+)([0-9a-fA-F]{2}(?::[0-9a-fA-F]{2}){5})").unwrap());
+
+/// One path in a probe snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    File(String),
+    /// Content that is not UTF-8, such as the SMBIOS table
+    Binary(Vec<u8>),
+    Dir,
+    Symlink(String),
+}
+
+/// A probe archive loaded into memory. Paths are absolute system paths
+/// (`/etc/os-release`, `/.probe/env`).
+pub struct Archive {
+    nodes: HashMap<String, Node>,
+}
+
+impl Archive {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open probe archive: {e}"))?;
+        Self::from_reader(file).map_err(|e| format!("Failed to read probe archive: {e}"))
+    }
+
+    fn from_reader(reader: impl Read) -> io::Result<Self> {
+        let mut archive = tar::Archive::new(reader);
+        let mut nodes = HashMap::new();
+        let mut total_size = 0;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = normalize(&entry.path()?.to_string_lossy());
+
+            let node = match entry.header().entry_type() {
+                tar::EntryType::Regular => {
+                    total_size += entry.size();
+                    if total_size > MAX_ARCHIVE_SIZE {
+                        return Err(io::Error::other("archive is too large"));
+                    }
+                    let mut content = Vec::new();
+                    entry.read_to_end(&mut content)?;
+                    match String::from_utf8(content) {
+                        Ok(content) => Node::File(content),
+                        Err(e) => Node::Binary(e.into_bytes()),
+                    }
+                }
+                tar::EntryType::Directory => Node::Dir,
+                tar::EntryType::Symlink => match entry.link_name()? {
+                    Some(target) => Node::Symlink(target.to_string_lossy().to_string()),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            nodes.insert(path, node);
+        }
+
+        Ok(Archive { nodes })
+    }
+
+    pub fn node(&self, path: &str) -> Option<&Node> {
+        self.nodes.get(&normalize(path))
+    }
+
+    pub fn read(&self, path: &str) -> Option<&str> {
+        match self.node(path)? {
+            Node::File(content) => Some(content),
+            _ => None,
+        }
+    }
+
+    pub fn read_bytes(&self, path: &str) -> Option<&[u8]> {
+        match self.node(path)? {
+            Node::File(content) => Some(content.as_bytes()),
+            Node::Binary(content) => Some(content),
+            _ => None,
+        }
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.node(path).is_some() || self.is_dir(path)
+    }
+
+    /// Directories exist either as their own entry or implicitly, through
+    /// the paths below them
+    pub fn is_dir(&self, path: &str) -> bool {
+        let path = normalize(path);
+        let prefix = if path == "/" {
+            path.clone()
+        } else {
+            format!("{path}/")
+        };
+        self.nodes.get(&path) == Some(&Node::Dir)
+            || self.nodes.keys().any(|key| key.starts_with(&prefix))
+    }
+
+    pub fn children(&self, path: &str) -> Option<Vec<DirEntry>> {
+        if !self.is_dir(path) {
+            return None;
+        }
+
+        let path = normalize(path);
+        let prefix = if path == "/" {
+            path
+        } else {
+            format!("{path}/")
+        };
+        let mut children: BTreeMap<&str, bool> = BTreeMap::new();
+
+        for (key, node) in &self.nodes {
+            let Some(rest) = key.strip_prefix(&prefix) else {
+                continue;
+            };
+            let (name, nested) = match rest.split_once('/') {
+                Some((name, _)) => (name, true),
+                None => (rest, *node == Node::Dir),
+            };
+            if !name.is_empty() {
+                *children.entry(name).or_default() |= nested;
+            }
+        }
+
+        Some(
+            children
+                .into_iter()
+                .map(|(name, is_dir)| DirEntry {
+                    name: name.to_string(),
+                    is_dir,
+                })
+                .collect(),
+        )
+    }
+}
+
+fn normalize(path: &str) -> String {
+    let trimmed = path
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .trim_end_matches('/');
+    format!("/{trimmed}")
+}
+
+/// Collects every input the detectors read while running against the host
+#[derive(Default)]
+pub struct Recorder {
+    nodes: BTreeMap<String, Node>,
+    env: BTreeMap<String, String>,
+    canonical: BTreeMap<String, String>,
+    statvfs: Vec<String>,
+    uname: Option<(String, String)>,
+    hostname: Option<String>,
+}
+
+impl Recorder {
+    pub fn add_file(&mut self, path: &str, content: &str) {
+        self.nodes
+            .insert(normalize(path), Node::File(content.to_string()));
+    }
+
+    pub fn add_bytes(&mut self, path: &str, content: &[u8]) {
+        let node = match std::str::from_utf8(content) {
+            Ok(content) => Node::File(content.to_string()),
+            Err(_) => Node::Binary(content.to_vec()),
+        };
+        self.nodes.insert(normalize(path), node);
+    }
+
+    pub fn add_listing(&mut self, path: &str, entries: &[DirEntry]) {
+        self.add_placeholder(path, true);
+        for entry in entries {
+            self.add_placeholder(&format!("{path}/{}", entry.name), entry.is_dir);
+        }
+    }
+
+    /// Record that a path exists without capturing its content
+    pub fn add_placeholder(&mut self, path: &str, is_dir: bool) {
+        let node = if is_dir {
+            Node::Dir
+        } else {
+            Node::File(String::new())
+        };
+        self.nodes.entry(normalize(path)).or_insert(node);
+    }
+
+    pub fn add_symlink(&mut self, path: &str, target: &str) {
+        self.nodes
+            .insert(normalize(path), Node::Symlink(target.to_string()));
+    }
+
+    pub fn add_canonical(&mut self, path: &str, resolved: &str) {
+        self.canonical
+            .insert(path.to_string(), resolved.to_string());
+    }
+
+    pub fn add_env(&mut self, key: &str, value: &str) {
+        self.env.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn add_command(&mut self, key: &str, stdout: &str) {
+        self.add_file(&format!("/{PROBE_DIR}/commands/{key}"), stdout);
+    }
+
+    pub fn add_statvfs(&mut self, line: &str) {
+        if !self.statvfs.iter().any(|l| l == line) {
+            self.statvfs.push(line.to_string());
+        }
+    }
+
+    pub fn set_uname(&mut self, release: &str, machine: &str) {
+        self.uname = Some((release.to_string(), machine.to_string()));
+    }
+
+    pub fn set_hostname(&mut self, hostname: &str) {
+        self.hostname = Some(hostname.to_string());
+    }
+
+    /// Turn the recording into snapshot nodes, with the hostname and network
+    /// addresses replaced
+    fn into_nodes(mut self) -> BTreeMap<String, Node> {
+        let probe = |name: &str| format!("/{PROBE_DIR}/{name}");
+
+        let env: String = self.env.iter().map(|(k, v)| format!("{k}={v}\n")).collect();
+        self.nodes.insert(probe("env"), Node::File(env));
+
+        if let Some((release, machine)) = &self.uname {
+            self.nodes.insert(
+                probe("uname"),
+                Node::File(format!("release={release}\nmachine={machine}\n")),
+            );
+        }
+
+        if !self.statvfs.is_empty() {
+            let content: String = self.statvfs.iter().map(|l| format!("{l}\n")).collect();
+            self.nodes.insert(probe("statvfs"), Node::File(content));
+        }
+
+        if !self.canonical.is_empty() {
+            let content: String = self
+                .canonical
+                .iter()
+                .map(|(from, to)| format!("{from}\t{to}\n"))
+                .collect();
+            self.nodes.insert(probe("canonical"), Node::File(content));
+        }
+
+        let hostname = self.hostname.take();
+        self.nodes.insert(
+            "/proc/sys/kernel/hostname".to_string(),
+            Node::File(format!("{REDACTED_HOSTNAME}\n")),
+        );
+
+        let mut addresses = HashMap::new();
+        for (path, node) in self.nodes.iter_mut() {
+            let Node::File(content) = node else {
+                continue;
+            };
+
+            // Only in files that name the machine, so that a hostname like
+            // "nixos" does not also rewrite os-release or store paths
+            let carries_hostname = path == "/etc/hostname"
+                || path == "/etc/hosts"
+                || path == "/proc/self/mountinfo"
+                || *path == probe("env");
+            if let Some(hostname) = &hostname
+                && hostname.len() >= 3
+                && carries_hostname
+            {
+                *content = content.replace(hostname.as_str(), REDACTED_HOSTNAME);
+            }
+
+            let carries_addresses = path == "/proc/self/mountinfo"
+                || path.starts_with(&format!("/{PROBE_DIR}/commands/ip "));
+            if carries_addresses {
+                *content = redact_addresses(content, &mut addresses);
+            }
+        }
+
+        self.nodes
+    }
+
+    /// Write the snapshot as a tar archive
+    pub fn write_archive(self, path: &Path) -> io::Result<()> {
+        let mut builder = tar::Builder::new(File::create(path)?);
+
+        for (path, node) in self.into_nodes() {
+            let name = path.trim_start_matches('/');
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut header = tar::Header::new_gnu();
+            header.set_mtime(0);
+            match node {
+                Node::File(content) => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(0o644);
+                    header.set_size(content.len() as u64);
+                    builder.append_data(&mut header, name, content.as_bytes())?;
+                }
+                Node::Binary(content) => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(0o644);
+                    header.set_size(content.len() as u64);
+                    builder.append_data(&mut header, name, content.as_slice())?;
+                }
+                Node::Dir => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    header.set_mode(0o755);
+                    header.set_size(0);
+                    builder.append_data(&mut header, name, io::empty())?;
+                }
+                Node::Symlink(target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_mode(0o777);
+                    header.set_size(0);
+                    builder.append_link(&mut header, name, target)?;
+                }
+            }
+        }
+
+        builder.into_inner()?;
+        Ok(())
+    }
+}
+
+/// Replace IPv4, IPv6 and MAC addresses with documentation addresses.
+/// Loopback stays as is so that detection still skips it, and every distinct
+/// address keeps a distinct replacement (for the first 131,834 IPv4
+/// addresses, far more than a machine has).
+fn redact_addresses(content: &str, seen: &mut HashMap<String, String>) -> String {
+    let mut next_replacement = |original: &str, make: &dyn Fn(usize) -> String| {
+        let n = seen.len() + 1;
+        seen.entry(original.to_string())
+            .or_insert_with(|| make(n))
+            .clone()
+    };
+
+    let content = IPV4_REGEX.replace_all(content, |caps: &regex::Captures| {
+        let ip = &caps[0];
+        if ip.starts_with("127.") || ip == "0.0.0.0" {
+            ip.to_string()
+        } else {
+            next_replacement(ip, &replacement_ipv4)
+        }
+    });
+
+    let content = IPV6_REGEX.replace_all(&content, |caps: &regex::Captures| {
+        let ip = &caps[2];
+        if ip == "::1" {
+            caps[0].to_string()
+        } else {
+            let replacement = next_replacement(ip, &|n| match n {
+                0..=0xffff => format!("2001:db8::{n:x}"),
+                _ => format!("2001:db8::{:x}:{:x}", n >> 16 & 0xffff, n & 0xffff),
+            });
+            format!("{}{replacement}", &caps[1])
+        }
+    });
+
+    let content = MAC_REGEX.replace_all(&content, |caps: &regex::Captures| {
+        let mac = &caps[2];
+        if mac == "00:00:00:00:00:00" {
+            caps[0].to_string()
+        } else {
+            let replacement = next_replacement(mac, &|n| {
+                let [a, b, c, d] = (n as u32).to_be_bytes();
+                format!("02:00:{a:02x}:{b:02x}:{c:02x}:{d:02x}")
+            });
+            format!("{}{replacement}", &caps[1])
+        }
+    });
+
+    content.to_string()
+}
+
+/// The `n`th replacement IPv4 address, counting from 1: the three
+/// documentation networks, then the benchmarking network 198.18.0.0/15
+fn replacement_ipv4(n: usize) -> String {
+    const DOCUMENTATION: [&str; 3] = ["192.0.2", "198.51.100", "203.0.113"];
+    let i = n - 1;
+
+    match DOCUMENTATION.get(i / 254) {
+        Some(network) => format!("{network}.{}", i % 254 + 1),
+        None => {
+            let i = i - DOCUMENTATION.len() * 254;
+            format!("198.{}.{}.{}", 18 + i / 65536 % 2, i / 256 % 256, i % 256)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_addresses() {
+        let output = "\
+1: lo: <LOOPBACK,UP,LOWER_UP> mtu 65536
+    link/loopback 00:00:00:00:00:00 brd 00:00:00:00:00:00
+    inet 127.0.0.1/8 scope host lo
+    inet6 ::1/128 scope host
+2: eth0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500
+    link/ether 52:54:00:12:34:56 brd ff:ff:ff:ff:ff:ff
+    inet 10.1.2.3/24 brd 10.1.2.255 scope global eth0
+    inet6 fe80::5054:ff:fe12:3456/64 scope link
+";
+        let redacted = redact_addresses(output, &mut HashMap::new());
+
+        assert!(redacted.contains("inet 127.0.0.1/8"));
+        assert!(redacted.contains("inet6 ::1/128"));
+        assert!(!redacted.contains("10.1.2.3"));
+        assert!(!redacted.contains("10.1.2.255"));
+        assert!(!redacted.contains("fe80::5054"));
+        assert!(!redacted.contains("52:54:00:12:34:56"));
+        assert!(redacted.contains("inet 192.0.2."));
+        assert!(redacted.contains("inet6 2001:db8::"));
+
+        // Replacements stay distinct past the first documentation network
+        let many: String = (0..1000)
+            .map(|i| format!("10.0.{}.{}\n", i / 250, i % 250 + 1))
+            .collect();
+        let redacted = redact_addresses(&many, &mut HashMap::new());
+        let distinct: std::collections::HashSet<&str> = redacted.lines().collect();
+        assert_eq!(distinct.len(), 1000);
+        assert!(redacted.contains("198.51.100.1\n") && redacted.contains("198.18.0.0\n"));
+    }
+
+    #[test]
+    fn test_archive_children() {
+        let mut nodes = HashMap::new();
+        nodes.insert("/var/lib/pacman/local/bash-5.2".to_string(), Node::Dir);
+        nodes.insert(
+            "/var/lib/pacman/local/zsh-5.9/desc".to_string(),
+            Node::File("%NAME%".to_string()),
+        );
+        nodes.insert(
+            "/var/lib/pacman/local/ALPM_DB_VERSION".to_string(),
+            Node::File("9".to_string()),
+        );
+        let archive = Archive { nodes };
+
+        assert!(archive.is_dir("/var/lib/pacman"));
+        assert!(archive.exists("/var/lib/pacman/local/zsh-5.9"));
+        assert!(!archive.exists("/var/lib/dpkg"));
+        assert_eq!(archive.children("/var/lib/dpkg"), None);
+        assert_eq!(
+            archive.children("/var/lib/pacman/local/").unwrap(),
+            vec![
+                DirEntry {
+                    name: "ALPM_DB_VERSION".to_string(),
+                    is_dir: false
+                },
+                DirEntry {
+                    name: "bash-5.2".to_string(),
+                    is_dir: true
+                },
+                DirEntry {
+                    name: "zsh-5.9".to_string(),
+                    is_dir: true
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::sysroot::SysRoot;
+use regex::Regex;
+use std::path::Path;
+
+/// How many ancestors to look through for the shell (e.g. past `sudo`).
+const MAX_ANCESTORS: usize = 8;
+
+/// Processes that commonly sit between the shell and us.
+const WRAPPERS: &[&str] = &[
+    "sudo", "doas", "su", "env", "nohup", "time", "script", "strace", "ltrace", "nice", "ionice",
+    "taskset", "chrt", "flock", "timeout", "watch", "xargs", "make", "cargo", "just",
+];
+
+/// How to find a shell's version, in order of preference
+struct VersionProbe {
+    name: &'static str,
+    /// Variable the shell sets to its own version (only seen when exported)
+    env: Option<&'static str>,
+    /// Flag that prints the version, if the shell has one
+    flag: Option<&'static str>,
+    /// Where the version sits in the variable or the flag output
+    pattern: &'static str,
+}
+
+const SHELLS: &[VersionProbe] = &[
+    VersionProbe {
+        name: "bash",
+        env: Some("BASH_VERSION"),
+        flag: Some("--version"),
+        pattern: r"(?:version )?(\d+\.\d+(?:\.\d+)?)",
+    },
+    VersionProbe {
+        name: "zsh",
+        env: Some("ZSH_VERSION"),
+        flag: Some("--version"),
+        pattern: r"^(?:zsh )?(\d+\.\d+(?:\.\d+)?)",
+    },
+    VersionProbe {
+        name: "fish",
+        env: Some("FISH_VERSION"),
+        flag: Some("--version"),
+        pattern: r"^(?:fish, version )?(\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "nu",
+        env: Some("NU_VERSION"),
+        flag: Some("--version"),
+        pattern: r"^(\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "elvish",
+        env: None,
+        flag: Some("--version"),
+        pattern: r"^v?(\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "xonsh",
+        env: Some("XONSH_VERSION"),
+        flag: Some("--version"),
+        pattern: r"^(?:xonsh/)?(\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "tcsh",
+        env: None,
+        flag: Some("--version"),
+        pattern: r"^tcsh (\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "pwsh",
+        env: None,
+        flag: Some("--version"),
+        pattern: r"^PowerShell (\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "yash",
+        env: Some("YASH_VERSION"),
+        flag: Some("--version"),
+        pattern: r"(?:version )?(\d+\.\d+\S*)",
+    },
+    VersionProbe {
+        name: "osh",
+        env: Some("OILS_VERSION"),
+        flag: Some("--version"),
+        pattern: r"^(?:Oils )?(\d+\.\d+\S*)",
+    },
+    // mksh and ksh93 only report their version through KSH_VERSION
+    VersionProbe {
+        name: "mksh",
+        env: Some("KSH_VERSION"),
+        flag: None,
+        pattern: r"(R\d+\w*)",
+    },
+    VersionProbe {
+        name: "ksh",
+        env: Some("KSH_VERSION"),
+        flag: None,
+        pattern: r"(?:93\w*\+?\w*/)?(\d+\.\d+\S*|R\d+\w*)",
+    },
+    VersionProbe {
+        name: "dash",
+        env: None,
+        flag: None,
+        pattern: "",
+    },
+    VersionProbe {
+        name: "sh",
+        env: None,
+        flag: None,
+        pattern: "",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shell {
+    pub name: String,
+    /// Executable path, when it could be determined
+    pub path: Option<String>,
+    pub version: Option<String>,
+}
+
+/// The shell we were started from, falling back to the login shell in
+/// `$SHELL`. Without exec (`--no-exec`) the version comes only from
+/// variables.
+pub fn detect_shell(root: &SysRoot) -> Option<Shell> {
+    let (name, path) = running_shell(root).or_else(|| {
+        let path = root.env("SHELL")?;
+        Some((executable_name(&path)?, Some(path)))
+    })?;
+
+    let version = probe(&name).and_then(|probe| {
+        let from_env = probe
+            .env
+            .and_then(|var| root.env(var))
+            .and_then(|value| extract(probe.pattern, &value));
+        from_env.or_else(|| {
+            let (flag, path) = (probe.flag?, path.as_deref()?);
+            extract(probe.pattern, &root.command(path, &[flag])?)
+        })
+    });
+
+    Some(Shell {
+        name,
+        path,
+        version,
+    })
+}
+
+fn probe(name: &str) -> Option<&'static VersionProbe> {
+    SHELLS.iter().find(|shell| shell.name == name)
+}
+
+fn extract(pattern: &str, text: &str) -> Option<String> {
+    let regex = Regex::new(&format!("(?m){pattern}")).ok()?;
+    Some(regex.captures(text.trim())?.get(1)?.as_str().to_string())
+}
+
+/// Walk up from our parent process to the first known shell, looking past
+/// wrappers like `sudo`. Gives up on anything else (a terminal, an IDE).
+fn running_shell(root: &SysRoot) -> Option<(String, Option<String>)> {
+    let mut pid = parent_pid(root, "self")?;
+
+    for _ in 0..MAX_ANCESTORS {
+        if pid <= 1 {
+            return None;
+        }
+
+        // The executable link is unreadable for other users' processes;
+        // comm still names the process there
+        let path = root
+            .read_link(&format!("/proc/{pid}/exe"))
+            .ok()
+            .map(|target| {
+                let target = target.to_string_lossy();
+                target.trim_end_matches(" (deleted)").to_string()
+            });
+        let name = match &path {
+            Some(path) => executable_name(path)?,
+            None => {
+                let comm = root.read_to_string(&format!("/proc/{pid}/comm")).ok()?;
+                executable_name(comm.trim())?
+            }
+        };
+
+        if probe(&name).is_some() {
+            // Without the executable, reuse $SHELL when it is the same shell
+            let path = path.or_else(|| {
+                root.env("SHELL")
+                    .filter(|shell| executable_name(shell).as_deref() == Some(name.as_str()))
+            });
+            return Some((name, path));
+        }
+        if !WRAPPERS.contains(&name.as_str()) {
+            return None;
+        }
+
+        pid = parent_pid(root, &pid.to_string())?;
+    }
+
+    None
+}
+
+/// Field 4 of `/proc/<pid>/stat`. The command name before it is in
+/// parentheses and may itself contain spaces and parentheses.
+fn parent_pid(root: &SysRoot, pid: &str) -> Option<u32> {
+    let stat = root.read_to_string(&format!("/proc/{pid}/stat")).ok()?;
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Login shells show up as `-bash`
+fn executable_name(path: &str) -> Option<String> {
+    let name = Path::new(path).file_name()?.to_string_lossy();
+    let name = name.trim_start_matches('-');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(shell: &str, text: &str) -> Option<String> {
+        extract(probe(shell).unwrap().pattern, text)
+    }
+
+    #[test]
+    fn test_version_patterns() {
+        assert_eq!(
+            version(
+                "bash",
+                "GNU bash, version 5.2.26(1)-release (x86_64-pc-linux-gnu)\nCopyright (C) 2022"
+            ),
+            Some("5.2.26".to_string())
+        );
+        assert_eq!(
+            version("bash", "5.2.26(1)-release"),
+            Some("5.2.26".to_string())
+        );
+        assert_eq!(
+            version("zsh", "zsh 5.9 (x86_64-pc-linux-gnu)"),
+            Some("5.9".to_string())
+        );
+        assert_eq!(
+            version("fish", "fish, version 3.7.1"),
+            Some("3.7.1".to_string())
+        );
+        assert_eq!(version("nu", "0.92.2\n"), Some("0.92.2".to_string()));
+        assert_eq!(
+            version("mksh", "@(#)MIRBSD KSH R59 2020/10/31"),
+            Some("R59".to_string())
+        );
+        assert_eq!(
+            version("ksh", "Version AJM 93u+m/1.0.8 2024-01-01"),
+            Some("1.0.8".to_string())
+        );
+        assert_eq!(version("zsh", "no version here"), None);
+    }
+
+    #[test]
+    fn test_executable_name() {
+        assert_eq!(executable_name("-bash"), Some("bash".to_string()));
+        assert_eq!(executable_name("/usr/bin/fish"), Some("fish".to_string()));
+        assert_eq!(executable_name("/"), None);
+    }
+}
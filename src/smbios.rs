@@ -0,0 +1,157 @@
+use crate::sysroot::SysRoot;
+
+/// The raw SMBIOS structure table exported by the kernel (readable by root only).
+const DMI_TABLE_PATH: &str = "/sys/firmware/dmi/tables/DMI";
+
+const END_OF_TABLE: u8 = 127;
+
+/// One SMBIOS structure: the formatted area (header included, so offsets
+/// match the specification) and the strings that follow it.
+#[derive(Debug, Clone)]
+pub struct Structure {
+    pub kind: u8,
+    data: Vec<u8>,
+    strings: Vec<String>,
+}
+
+impl Structure {
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        self.data.get(offset).copied()
+    }
+
+    pub fn word(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn dword(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// The string referenced by the byte at `offset`, if set and not blank
+    pub fn string(&self, offset: usize) -> Option<&str> {
+        let index = self.byte(offset)? as usize;
+        let value = self.strings.get(index.checked_sub(1)?)?.trim();
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// Split a raw table into its structures. Truncated trailing data is ignored.
+pub fn parse_table(table: &[u8]) -> Vec<Structure> {
+    let mut structures = Vec::new();
+    let mut pos = 0;
+
+    while pos + 4 <= table.len() {
+        let kind = table[pos];
+        let length = table[pos + 1] as usize;
+        if length < 4 || pos + length > table.len() {
+            break;
+        }
+
+        let data = table[pos..pos + length].to_vec();
+        let mut strings = Vec::new();
+        let mut cursor = pos + length;
+
+        // The string set ends with an empty string; a structure without
+        // strings still has the two terminating zeros.
+        loop {
+            let Some(len) = table[cursor.min(table.len())..]
+                .iter()
+                .position(|&b| b == 0)
+            else {
+                return structures;
+            };
+            if len == 0 {
+                cursor += 1;
+                if strings.is_empty() && table.get(cursor) == Some(&0) {
+                    cursor += 1;
+                }
+                break;
+            }
+            strings.push(String::from_utf8_lossy(&table[cursor..cursor + len]).to_string());
+            cursor += len + 1;
+        }
+
+        structures.push(Structure {
+            kind,
+            data,
+            strings,
+        });
+
+        if kind == END_OF_TABLE {
+            break;
+        }
+        pos = cursor;
+    }
+
+    structures
+}
+
+/// Every structure in the table, or none when it is unreadable
+pub fn read_table(root: &SysRoot) -> Vec<Structure> {
+    root.read(DMI_TABLE_PATH)
+        .map(|table| parse_table(&table))
+        .unwrap_or_default()
+}
+
+/// All structures of the given type
+pub fn read_structures(root: &SysRoot, kind: u8) -> Vec<Structure> {
+    read_table(root)
+        .into_iter()
+        .filter(|s| s.kind == kind)
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Build a structure the way firmware lays it out
+    pub fn structure(kind: u8, formatted: &[u8], strings: &[&str]) -> Vec<u8> {
+        let mut bytes = vec![kind, formatted.len() as u8 + 4, 0, 0];
+        bytes.extend_from_slice(formatted);
+        for s in strings {
+            bytes.extend_from_slice(s.as_bytes());
+            bytes.push(0);
+        }
+        if strings.is_empty() {
+            bytes.push(0);
+        }
+        bytes.push(0);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let mut table = structure(0, &[1, 2, 0, 0], &["American Megatrends", "1.0"]);
+        table.extend(structure(32, &[0; 7], &[]));
+        table.extend(structure(1, &[2, 1], &["  "]));
+        table.extend(structure(END_OF_TABLE, &[], &[]));
+        table.extend(structure(0, &[], &["after the end"]));
+
+        let structures = parse_table(&table);
+        assert_eq!(
+            structures.iter().map(|s| s.kind).collect::<Vec<_>>(),
+            vec![0, 32, 1, END_OF_TABLE]
+        );
+
+        let bios = &structures[0];
+        assert_eq!(bios.string(4), Some("American Megatrends"));
+        assert_eq!(bios.string(5), Some("1.0"));
+        assert_eq!(bios.string(6), None);
+        assert_eq!(bios.word(4), Some(0x0201));
+        assert_eq!(bios.dword(6), None);
+
+        assert_eq!(structures[2].string(4), None);
+        assert_eq!(structures[2].string(5), None);
+    }
+
+    #[test]
+    fn test_parse_truncated_table() {
+        let table = structure(17, &[0; 30], &["DIMM A1"]);
+        assert_eq!(parse_table(&table[..20]).len(), 0);
+        assert_eq!(parse_table(&table[..table.len() - 1]).len(), 0);
+        assert_eq!(parse_table(&table).len(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::parse_color;
+use crossterm::style::Color;
+
+/// Text attributes that can be switched on
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Attributes {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+    pub strikethrough: bool,
+}
+
+impl Attributes {
+    const BOLD: Attributes = Attributes {
+        bold: true,
+        dim: false,
+        italic: false,
+        underline: false,
+        reverse: false,
+        strikethrough: false,
+    };
+
+    /// Switch on the attribute called `name`; false for unknown names
+    fn set(&mut self, name: &str) -> bool {
+        let attribute = match name {
+            "bold" => &mut self.bold,
+            "dim" => &mut self.dim,
+            "italic" => &mut self.italic,
+            "underline" => &mut self.underline,
+            "reverse" => &mut self.reverse,
+            "strikethrough" | "strike" => &mut self.strikethrough,
+            _ => return false,
+        };
+        *attribute = true;
+        true
+    }
+
+    fn codes(&self) -> Vec<&'static str> {
+        [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+            (self.strikethrough, "9"),
+        ]
+        .into_iter()
+        .filter_map(|(set, code)| set.then_some(code))
+        .collect()
+    }
+
+    /// Whether every attribute of `other` is also set here
+    fn contains(&self, other: &Attributes) -> bool {
+        other.codes().iter().all(|code| self.codes().contains(code))
+    }
+}
+
+/// Colors and attributes for a piece of text, as in
+/// `bold;italic;fg=#ff8800;bg=236`. Anything left out is taken from the
+/// style it is layered over.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    /// `None` when the spec named no attributes
+    pub attributes: Option<Attributes>,
+}
+
+impl Style {
+    /// Keys and titles: bold
+    pub const KEY: Style = Style {
+        fg: None,
+        bg: None,
+        attributes: Some(Attributes::BOLD),
+    };
+
+    /// Logo colors: bold, unless the logo or the user says otherwise
+    pub fn logo(color: Color) -> Style {
+        Style {
+            fg: Some(color),
+            ..Style::KEY
+        }
+    }
+
+    /// Parse `;`-separated attributes (`bold`, `dim`, `italic`, `underline`,
+    /// `reverse`, `strikethrough`, or `normal` for none), `fg=COLOR`,
+    /// `bg=COLOR` and bare colors, which set the foreground
+    pub fn parse(spec: &str) -> Result<Style, String> {
+        let mut style = Style::default();
+
+        for token in spec.split(';').map(str::trim).filter(|t| !t.is_empty()) {
+            let color = |value: &str| parse_color(value).ok_or(format!("invalid color '{value}'"));
+            let name = token.to_lowercase();
+            let mut attributes = style.attributes.unwrap_or_default();
+
+            if name == "normal" {
+                style.attributes = Some(Attributes::default());
+            } else if attributes.set(&name) {
+                style.attributes = Some(attributes);
+            } else if let Some(value) = token.strip_prefix("fg=") {
+                style.fg = Some(color(value)?);
+            } else if let Some(value) = token.strip_prefix("bg=") {
+                style.bg = Some(color(value)?);
+            } else {
+                style.fg = Some(parse_color(token).ok_or(format!("invalid style '{token}'"))?);
+            }
+        }
+
+        Ok(style)
+    }
+
+    /// This style with whatever it leaves out taken from `base`
+    pub fn over(self, base: Style) -> Style {
+        Style {
+            fg: self.fg.or(base.fg),
+            bg: self.bg.or(base.bg),
+            attributes: self.attributes.or(base.attributes),
+        }
+    }
+
+    /// This style without its attributes, keeping only the colors
+    pub fn colors_only(self) -> Style {
+        Style {
+            attributes: None,
+            ..self
+        }
+    }
+
+    /// The escape sequence that turns this style on; empty for no style
+    pub fn sgr(&self) -> String {
+        let mut params: Vec<String> = self
+            .attributes
+            .unwrap_or_default()
+            .codes()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        params.extend(self.fg.map(|color| color_params(color, false)));
+        params.extend(self.bg.map(|color| color_params(color, true)));
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", params.join(";"))
+        }
+    }
+
+    /// The escape sequence that switches from `previous` to this style,
+    /// resetting first when something of `previous` would otherwise linger
+    pub fn switch_from(&self, previous: &Style) -> String {
+        let attributes = self.attributes.unwrap_or_default();
+        let lingers = !attributes.contains(&previous.attributes.unwrap_or_default())
+            || (previous.bg.is_some() && self.bg.is_none());
+
+        if lingers {
+            format!("\x1b[0m{}", self.sgr())
+        } else {
+            self.sgr()
+        }
+    }
+}
+
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
+        Style {
+            fg: Some(color),
+            ..Style::default()
+        }
+    }
+}
+
+/// SGR parameters selecting `color` as foreground or background
+fn color_params(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+    let extended = if background { 48 } else { 38 };
+
+    let code = match color {
+        Color::Black => 30,
+        Color::DarkRed => 31,
+        Color::DarkGreen => 32,
+        Color::DarkYellow => 33,
+        Color::DarkBlue => 34,
+        Color::DarkMagenta => 35,
+        Color::DarkCyan => 36,
+        Color::Grey => 37,
+        Color::DarkGrey => 90,
+        Color::Red => 91,
+        Color::Green => 92,
+        Color::Yellow => 93,
+        Color::Blue => 94,
+        Color::Magenta => 95,
+        Color::Cyan => 96,
+        Color::White => 97,
+        Color::AnsiValue(n) => return format!("{extended};5;{n}"),
+        Color::Rgb { r, g, b } => return format!("{extended};2;{r};{g};{b}"),
+        _ => 39,
+    };
+    (code + offset).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        let style = Style::parse("bold;italic;fg=#ff8800;bg=236").unwrap();
+        assert_eq!(
+            style.fg,
+            Some(Color::Rgb {
+                r: 255,
+                g: 136,
+                b: 0
+            })
+        );
+        assert_eq!(style.bg, Some(Color::AnsiValue(236)));
+        assert_eq!(style.sgr(), "\x1b[1;3;38;2;255;136;0;48;5;236m");
+
+        assert_eq!(Style::parse("red").unwrap().sgr(), "\x1b[91m");
+        assert_eq!(
+            Style::parse("Underline; bg=blue").unwrap().sgr(),
+            "\x1b[4;104m"
+        );
+        assert_eq!(Style::parse("").unwrap(), Style::default());
+        assert_eq!(Style::parse("normal").unwrap().sgr(), "");
+
+        assert!(Style::parse("bold;sparkly").is_err());
+        assert!(Style::parse("fg=#12345").is_err());
+    }
+
+    #[test]
+    fn test_layering_and_switching() {
+        let logo = Style::logo(Color::Blue);
+        assert_eq!(Style::parse("red").unwrap().over(logo).sgr(), "\x1b[1;91m");
+        assert_eq!(
+            Style::parse("normal;red").unwrap().over(logo).sgr(),
+            "\x1b[91m"
+        );
+        assert_eq!(logo.colors_only().sgr(), "\x1b[94m");
+
+        let italic = Style::parse("italic").unwrap().over(logo);
+        assert_eq!(italic.switch_from(&logo), "\x1b[0m\x1b[3;94m");
+        assert_eq!(Style::logo(Color::White).switch_from(&logo), "\x1b[1;97m");
+        let highlighted = Style::parse("bg=red").unwrap().over(logo);
+        assert_eq!(logo.switch_from(&highlighted), "\x1b[0m\x1b[1;94m");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::memory::{self, ZramDevice};
+use crate::sysroot::SysRoot;
+
+const ZSWAP_PARAMETERS: &str = "/sys/module/zswap/parameters";
+
+/// One active swap area, as listed in `/proc/swaps`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapDevice {
+    /// Device or file path; empty for the meminfo total when `/proc/swaps`
+    /// is unavailable
+    pub path: String,
+    /// `partition` or `file`
+    pub kind: String,
+    pub size: u64,
+    pub used: u64,
+    pub priority: Option<i32>,
+    pub zram: Option<ZramDevice>,
+}
+
+impl SwapDevice {
+    pub fn percent(&self) -> u32 {
+        if self.size == 0 {
+            return 0;
+        }
+        (self.used as f64 / self.size as f64 * 100.0) as u32
+    }
+}
+
+/// Swap areas in use. `None` when neither `/proc/swaps` nor `/proc/meminfo`
+/// can be read; an empty list means swap is disabled.
+pub fn detect_swaps(root: &SysRoot) -> Option<Vec<SwapDevice>> {
+    let Ok(swaps) = root.read_to_string("/proc/swaps") else {
+        return meminfo_total(root);
+    };
+
+    let zram = memory::detect_zram(root);
+    Some(
+        parse_swaps(&swaps)
+            .into_iter()
+            .map(|mut device| {
+                let name = device.path.strip_prefix("/dev/").unwrap_or_default();
+                device.zram = zram.iter().find(|z| z.name == name).cloned();
+                device
+            })
+            .collect(),
+    )
+}
+
+/// The aggregate from `/proc/meminfo`, for snapshots without `/proc/swaps`
+fn meminfo_total(root: &SysRoot) -> Option<Vec<SwapDevice>> {
+    let info = memory::read_meminfo(root)?;
+    if info.swap_total == 0 {
+        return Some(Vec::new());
+    }
+
+    Some(vec![SwapDevice {
+        path: String::new(),
+        kind: String::new(),
+        size: info.swap_total,
+        used: info.swap_total.saturating_sub(info.swap_free),
+        priority: None,
+        zram: None,
+    }])
+}
+
+fn parse_swaps(swaps: &str) -> Vec<SwapDevice> {
+    swaps
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 5 {
+                return None;
+            }
+
+            Some(SwapDevice {
+                path: fields[0].replace("\\040", " "),
+                kind: fields[1].to_string(),
+                size: fields[2].parse::<u64>().ok()? * 1024,
+                used: fields[3].parse::<u64>().ok()? * 1024,
+                priority: fields[4].parse().ok(),
+                zram: None,
+            })
+        })
+        .collect()
+}
+
+/// Configuration of the compressed swap cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZswapStatus {
+    pub enabled: bool,
+    pub compressor: Option<String>,
+    pub zpool: Option<String>,
+    pub max_pool_percent: Option<u32>,
+}
+
+/// `None` when the kernel has no zswap support
+pub fn detect_zswap(root: &SysRoot) -> Option<ZswapStatus> {
+    let parameter = |name: &str| {
+        root.read_to_string(&format!("{ZSWAP_PARAMETERS}/{name}"))
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let enabled = parameter("enabled")?;
+    Some(ZswapStatus {
+        enabled: matches!(enabled.as_str(), "Y" | "y" | "1"),
+        compressor: parameter("compressor"),
+        zpool: parameter("zpool"),
+        max_pool_percent: parameter("max_pool_percent").and_then(|v| v.parse().ok()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_swaps() {
+        let swaps = "\
+Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority
+/dev/nvme0n1p3                          partition\t8388604\t\t388604\t\t-2
+/var/lib/my\\040swap                     file\t\t1048572\t\t0\t\t-3
+/dev/zram0                              partition\t4194300\t\t524288\t\t100
+";
+        let devices = parse_swaps(swaps);
+
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].path, "/dev/nvme0n1p3");
+        assert_eq!(devices[0].kind, "partition");
+        assert_eq!(devices[0].used, 388604 * 1024);
+        assert_eq!(devices[0].priority, Some(-2));
+        assert_eq!(devices[0].percent(), 4);
+        assert_eq!(devices[1].path, "/var/lib/my swap");
+        assert_eq!(devices[1].kind, "file");
+        assert_eq!(devices[2].priority, Some(100));
+        assert_eq!(devices[2].percent(), 12);
+
+        assert!(parse_swaps("Filename Type Size Used Priority\n").is_empty());
+    }
+}
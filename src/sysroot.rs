@@ -0,0 +1,456 @@
+use crate::disk::FsStats;
+use crate::exec::{self, Limits};
+use crate::probe::{Archive, Node, Recorder};
+use nix::sys::statvfs::statvfs;
+use nix::sys::utsname::uname;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+/// Directory inside a snapshot holding the inputs that are not files:
+/// environment, uname, statvfs results and command output.
+pub const PROBE_DIR: &str = ".probe";
+
+/// Everything the detectors read from the system goes through here, so that
+/// a captured snapshot of `/proc`, `/sys` and `/etc` can stand in for the
+/// running machine.
+pub struct SysRoot {
+    backend: Backend,
+    env: HashMap<String, String>,
+    recorder: Option<Mutex<Recorder>>,
+    exec: bool,
+}
+
+enum Backend {
+    Host,
+    Dir(PathBuf),
+    Archive(Archive),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+impl SysRoot {
+    /// The running system
+    pub fn host() -> Self {
+        SysRoot {
+            backend: Backend::Host,
+            env: HashMap::new(),
+            recorder: None,
+            exec: true,
+        }
+    }
+
+    /// Remember every input read from this root, for `--dump-probe`
+    pub fn recording(self) -> Self {
+        SysRoot {
+            recorder: Some(Mutex::new(Recorder::default())),
+            ..self
+        }
+    }
+
+    /// Never run external commands, for `--no-exec`
+    pub fn without_exec(self) -> Self {
+        SysRoot {
+            exec: false,
+            ..self
+        }
+    }
+
+    /// A snapshot directory laid out like `/`, with a `.probe` directory for
+    /// the non-file inputs
+    pub fn fixture(root: impl Into<PathBuf>) -> Self {
+        Self::with_probe_env(Backend::Dir(root.into()))
+    }
+
+    /// A snapshot written by `--dump-probe`
+    pub fn from_archive(path: &Path) -> Result<Self, String> {
+        Ok(Self::with_probe_env(Backend::Archive(Archive::load(path)?)))
+    }
+
+    fn with_probe_env(backend: Backend) -> Self {
+        let mut root = SysRoot {
+            backend,
+            env: HashMap::new(),
+            recorder: None,
+            exec: true,
+        };
+        root.env = root
+            .probe_file("env")
+            .map(|content| parse_key_values(&content))
+            .unwrap_or_default();
+        root
+    }
+
+    /// Map an absolute system path onto the filesystem. Archives have no
+    /// filesystem location, so their paths map to themselves.
+    pub fn path(&self, path: &str) -> PathBuf {
+        match &self.backend {
+            Backend::Dir(root) => root.join(path.trim_start_matches('/')),
+            Backend::Host | Backend::Archive(_) => PathBuf::from(path),
+        }
+    }
+
+    /// The recorded inputs, once detection has run on a recording root
+    pub fn take_recording(&self) -> Option<Recorder> {
+        let recorder = self.recorder.as_ref()?;
+        Some(std::mem::take(&mut *recorder.lock().ok()?))
+    }
+
+    fn record(&self, f: impl FnOnce(&mut Recorder)) {
+        if let Some(recorder) = &self.recorder
+            && let Ok(mut recorder) = recorder.lock()
+        {
+            f(&mut recorder);
+        }
+    }
+
+    fn probe_file(&self, name: &str) -> Option<String> {
+        let path = format!("/{PROBE_DIR}/{name}");
+        match &self.backend {
+            Backend::Host => None,
+            Backend::Dir(_) => fs::read_to_string(self.path(&path)).ok(),
+            Backend::Archive(archive) => archive.read(&path).map(|s| s.to_string()),
+        }
+    }
+
+    pub fn read_to_string(&self, path: &str) -> io::Result<String> {
+        let content = match &self.backend {
+            Backend::Archive(archive) => archive
+                .read(path)
+                .map(|s| s.to_string())
+                .ok_or_else(|| not_found(path)),
+            _ => fs::read_to_string(self.path(path)),
+        }?;
+
+        self.record(|r| r.add_file(path, &content));
+        Ok(content)
+    }
+
+    /// Raw file content, for binary tables like SMBIOS
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let content = match &self.backend {
+            Backend::Archive(archive) => archive
+                .read_bytes(path)
+                .map(|s| s.to_vec())
+                .ok_or_else(|| not_found(path)),
+            _ => fs::read(self.path(path)),
+        }?;
+
+        self.record(|r| r.add_bytes(path, &content));
+        Ok(content)
+    }
+
+    pub fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        let mut entries: Vec<DirEntry> = match &self.backend {
+            Backend::Archive(archive) => archive.children(path).ok_or_else(|| not_found(path))?,
+            _ => fs::read_dir(self.path(path))?
+                .flatten()
+                .map(|entry| DirEntry {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    is_dir: entry.file_type().map(|t| t.is_dir()).unwrap_or(false),
+                })
+                .collect(),
+        };
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.record(|r| r.add_listing(path, &entries));
+        Ok(entries)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        let (exists, is_dir) = match &self.backend {
+            Backend::Archive(archive) => (archive.exists(path), archive.is_dir(path)),
+            _ => {
+                let path = self.path(path);
+                (path.exists(), path.is_dir())
+            }
+        };
+
+        if exists {
+            self.record(|r| r.add_placeholder(path, is_dir));
+        }
+        exists
+    }
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        let is_dir = match &self.backend {
+            Backend::Archive(archive) => archive.is_dir(path),
+            _ => self.path(path).is_dir(),
+        };
+
+        if is_dir {
+            self.record(|r| r.add_placeholder(path, true));
+        }
+        is_dir
+    }
+
+    pub fn read_link(&self, path: &str) -> io::Result<PathBuf> {
+        let target = match &self.backend {
+            Backend::Archive(archive) => match archive.node(path) {
+                Some(Node::Symlink(target)) => Ok(PathBuf::from(target)),
+                _ => Err(not_found(path)),
+            },
+            _ => fs::read_link(self.path(path)),
+        }?;
+
+        self.record(|r| r.add_symlink(path, &target.to_string_lossy()));
+        Ok(target)
+    }
+
+    /// Resolve symlinks, returning an absolute path inside this root
+    pub fn canonicalize(&self, path: &str) -> io::Result<String> {
+        let recorded = self.probe_file("canonical").and_then(|content| {
+            content.lines().find_map(|line| {
+                let (from, to) = line.split_once('\t')?;
+                (from == path).then(|| to.to_string())
+            })
+        });
+
+        let resolved = match (&self.backend, recorded) {
+            (_, Some(resolved)) => resolved,
+            (Backend::Host, None) => fs::canonicalize(path)?.to_string_lossy().to_string(),
+            (Backend::Dir(root), None) => {
+                let resolved = fs::canonicalize(self.path(path))?;
+                let root = fs::canonicalize(root)?;
+                let inner = resolved
+                    .strip_prefix(&root)
+                    .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "link leaves the root"))?;
+                Path::new("/").join(inner).to_string_lossy().to_string()
+            }
+            (Backend::Archive(_), None) => return Err(not_found(path)),
+        };
+
+        self.record(|r| r.add_canonical(path, &resolved));
+        Ok(resolved)
+    }
+
+    pub fn env(&self, key: &str) -> Option<String> {
+        let value = match self.backend {
+            Backend::Host => env::var(key).ok(),
+            _ => self.env.get(key).cloned(),
+        };
+
+        if let Some(value) = &value {
+            self.record(|r| r.add_env(key, value));
+        }
+        value
+    }
+
+    /// Standard output of a command that exited successfully, run through
+    /// the hardened runner in `exec`
+    pub fn command(&self, program: &str, args: &[&str]) -> Option<String> {
+        if !self.exec {
+            return None;
+        }
+
+        let key = command_key(program, args);
+        let stdout = match self.backend {
+            Backend::Host => exec::run(program, args, &Limits::default())?,
+            _ => self.probe_file(&format!("commands/{key}"))?,
+        };
+
+        self.record(|r| r.add_command(&key, &stdout));
+        Some(stdout)
+    }
+
+    /// Filesystem counters for a mount point. With a timeout the call is made
+    /// on a helper thread, for mounts that may hang (network filesystems).
+    pub fn statvfs(&self, path: &str, timeout: Option<Duration>) -> Option<FsStats> {
+        let stats = match (&self.backend, timeout) {
+            (Backend::Dir(_) | Backend::Archive(_), _) => self
+                .probe_file("statvfs")?
+                .lines()
+                .find_map(|line| parse_statvfs_line(line, path)),
+            (Backend::Host, None) => query_statvfs(path),
+            (Backend::Host, Some(timeout)) => {
+                let (tx, rx) = mpsc::channel();
+                let owned = path.to_string();
+                thread::spawn(move || {
+                    let _ = tx.send(query_statvfs(&owned));
+                });
+                rx.recv_timeout(timeout).ok().flatten()
+            }
+        };
+
+        if let Some(stats) = &stats {
+            self.record(|r| r.add_statvfs(&format_statvfs_line(path, stats)));
+        }
+        stats
+    }
+
+    /// Kernel release and machine architecture
+    pub fn uname(&self) -> Option<(String, String)> {
+        let (release, machine) = match self.backend {
+            Backend::Host => {
+                let info = uname().ok()?;
+                (
+                    info.release().to_string_lossy().to_string(),
+                    info.machine().to_string_lossy().to_string(),
+                )
+            }
+            _ => {
+                let values = parse_key_values(&self.probe_file("uname")?);
+                (
+                    values.get("release")?.clone(),
+                    values.get("machine")?.clone(),
+                )
+            }
+        };
+
+        self.record(|r| r.set_uname(&release, &machine));
+        Some((release, machine))
+    }
+
+    pub fn hostname(&self) -> String {
+        let hostname = match self.backend {
+            Backend::Host => gethostname::gethostname().to_string_lossy().to_string(),
+            _ => fs_hostname(self),
+        };
+
+        self.record(|r| r.set_hostname(&hostname));
+        hostname
+    }
+}
+
+/// Snapshots carry the hostname the same place the kernel exposes it
+fn fs_hostname(root: &SysRoot) -> String {
+    let content = match &root.backend {
+        Backend::Archive(archive) => archive
+            .read("/proc/sys/kernel/hostname")
+            .map(str::to_string),
+        _ => fs::read_to_string(root.path("/proc/sys/kernel/hostname")).ok(),
+    };
+
+    content
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{path} not in snapshot"))
+}
+
+fn query_statvfs(path: &str) -> Option<FsStats> {
+    let stat = statvfs(path).ok()?;
+    Some(FsStats {
+        fragment_size: stat.fragment_size() as u64,
+        blocks: stat.blocks() as u64,
+        blocks_free: stat.blocks_free() as u64,
+        blocks_available: stat.blocks_available() as u64,
+        files: stat.files() as u64,
+        files_free: stat.files_free() as u64,
+    })
+}
+
+/// `<mount point> <fragment size> <blocks> <free> <available> <files> <files free>`,
+/// with spaces in the mount point written as `\040` like in mountinfo
+fn parse_statvfs_line(line: &str, path: &str) -> Option<FsStats> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 7 || fields[0] != path.replace(' ', "\\040") {
+        return None;
+    }
+
+    let values: Vec<u64> = fields[1..]
+        .iter()
+        .map(|v| v.parse().ok())
+        .collect::<Option<_>>()?;
+    Some(FsStats {
+        fragment_size: values[0],
+        blocks: values[1],
+        blocks_free: values[2],
+        blocks_available: values[3],
+        files: values[4],
+        files_free: values[5],
+    })
+}
+
+fn format_statvfs_line(path: &str, stats: &FsStats) -> String {
+    format!(
+        "{} {} {} {} {} {} {}",
+        path.replace(' ', "\\040"),
+        stats.fragment_size,
+        stats.blocks,
+        stats.blocks_free,
+        stats.blocks_available,
+        stats.files,
+        stats.files_free
+    )
+}
+
+fn parse_key_values(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// File name under `.probe/commands` for a command line. `/` cannot appear
+/// in a file name, so it is percent-encoded along with `%` itself.
+pub fn command_key(program: &str, args: &[&str]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('%', "%25")
+        .replace('/', "%2F")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_key() {
+        assert_eq!(command_key("ip", &["addr", "show"]), "ip addr show");
+        assert_eq!(
+            command_key("nix-store", &["--query", "/run/current-system"]),
+            "nix-store --query %2Frun%2Fcurrent-system"
+        );
+        assert_eq!(command_key("/bin/50%", &[]), "%2Fbin%2F50%25");
+    }
+
+    #[test]
+    fn test_parse_statvfs_line() {
+        let line = "/home 4096 1000 300 250 64 16";
+        assert_eq!(parse_statvfs_line(line, "/"), None);
+        assert_eq!(
+            parse_statvfs_line(line, "/home"),
+            Some(FsStats {
+                fragment_size: 4096,
+                blocks: 1000,
+                blocks_free: 300,
+                blocks_available: 250,
+                files: 64,
+                files_free: 16,
+            })
+        );
+        assert_eq!(parse_statvfs_line("/home 4096 x", "/home"), None);
+
+        let stats = parse_statvfs_line(line, "/home").unwrap();
+        assert_eq!(format_statvfs_line("/home", &stats), line);
+        assert_eq!(
+            parse_statvfs_line(&format_statvfs_line("/mnt/my disk", &stats), "/mnt/my disk"),
+            Some(stats)
+        );
+    }
+
+    #[test]
+    fn test_fixture_paths() {
+        let root = SysRoot::fixture("tests/fixtures/debian");
+        assert_eq!(
+            root.path("/etc/os-release"),
+            PathBuf::from("tests/fixtures/debian/etc/os-release")
+        );
+        assert_eq!(SysRoot::host().path("/proc"), PathBuf::from("/proc"));
+    }
+}
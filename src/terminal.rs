@@ -0,0 +1,286 @@
+use crate::{layout, palette};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+/// When to color the output
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice {
+    /// Only on a terminal, unless NO_COLOR, CLICOLOR_FORCE or TERM=dumb say otherwise
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How many colors the terminal can show
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum ColorDepth {
+    /// The 16 base colors
+    #[value(name = "16")]
+    Ansi16,
+    /// The xterm 256-color palette
+    #[value(name = "256")]
+    Ansi256,
+    /// 24-bit RGB
+    #[value(name = "truecolor", alias = "24bit")]
+    TrueColor,
+}
+
+/// What the output can use: its width, whether colors are wanted and how
+/// many there are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Terminal {
+    /// Columns; `None` when output is piped
+    pub width: Option<usize>,
+    /// Rows; `None` when output is piped
+    pub height: Option<usize>,
+    pub color: bool,
+    pub depth: ColorDepth,
+}
+
+impl Default for Terminal {
+    /// No width limit, colored in 24-bit
+    fn default() -> Self {
+        Terminal {
+            width: None,
+            height: None,
+            color: true,
+            depth: ColorDepth::TrueColor,
+        }
+    }
+}
+
+impl Terminal {
+    /// The terminal on stdout, if any, and the color settings from the
+    /// environment
+    pub fn detect(choice: ColorChoice, depth: Option<ColorDepth>) -> Self {
+        let is_terminal = io::stdout().is_terminal();
+        let size = is_terminal
+            .then(|| crossterm::terminal::size().ok())
+            .flatten()
+            .filter(|&(columns, rows)| columns > 0 && rows > 0);
+
+        let var = |name: &str| env::var(name).ok();
+
+        Terminal {
+            width: size.map(|(columns, _)| columns as usize),
+            height: size.map(|(_, rows)| rows as usize),
+            color: color_enabled(choice, is_terminal, var),
+            depth: depth.unwrap_or_else(|| color_depth(var, terminfo_colors)),
+        }
+    }
+}
+
+impl Terminal {
+    /// `text` with its escape sequences removed without colors, or its
+    /// colors brought down to the ones the terminal has
+    pub fn adapt(&self, text: &str) -> String {
+        if self.color {
+            palette::downsample(text, self.depth)
+        } else {
+            layout::strip_escapes(text)
+        }
+    }
+}
+
+/// Whether to color output for `choice`. With `auto`, a non-empty NO_COLOR
+/// turns colors off, CLICOLOR_FORCE (other than `0`) forces them on, and
+/// otherwise only a terminal other than TERM=dumb gets them.
+pub fn color_enabled(
+    choice: ColorChoice,
+    is_terminal: bool,
+    var: impl Fn(&str) -> Option<String>,
+) -> bool {
+    let set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+    let forced = set("CLICOLOR_FORCE") && var("CLICOLOR_FORCE").as_deref() != Some("0");
+
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto if set("NO_COLOR") => false,
+        ColorChoice::Auto if forced => true,
+        ColorChoice::Auto => is_terminal && var("TERM").as_deref() != Some("dumb"),
+    }
+}
+
+/// Colors the terminal supports, from COLORTERM, then the terminfo entry
+/// for TERM, then the TERM name itself. Unknown terminals get 16 colors.
+pub fn color_depth(
+    var: impl Fn(&str) -> Option<String>,
+    terminfo_colors: impl Fn(&str) -> Option<u32>,
+) -> ColorDepth {
+    if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) {
+        return ColorDepth::TrueColor;
+    }
+
+    let term = var("TERM").unwrap_or_default();
+    match terminfo_colors(&term) {
+        Some(colors) if colors >= 1 << 24 => ColorDepth::TrueColor,
+        Some(colors) if colors >= 256 => ColorDepth::Ansi256,
+        Some(_) => ColorDepth::Ansi16,
+        None if term.ends_with("-direct") => ColorDepth::TrueColor,
+        None if term.contains("256color") => ColorDepth::Ansi256,
+        None => ColorDepth::Ansi16,
+    }
+}
+
+/// The `colors` capability of the compiled terminfo entry for `term`
+fn terminfo_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    if term.contains('/') {
+        return None;
+    }
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    dirs.extend(env::var_os("TERMINFO").map(PathBuf::from));
+    dirs.extend(env::var_os("HOME").map(|home| PathBuf::from(home).join(".terminfo")));
+    if let Some(list) = env::var_os("TERMINFO_DIRS") {
+        dirs.extend(env::split_paths(&list).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    dirs.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    dirs.iter()
+        .flat_map(|dir| {
+            // Letter directories on Linux, hexadecimal ones on macOS
+            [
+                dir.join(first.to_string()).join(term),
+                dir.join(format!("{:x}", first as u32)).join(term),
+            ]
+        })
+        .find_map(|path| fs::read(path).ok())
+        .and_then(|entry| parse_terminfo_colors(&entry))
+}
+
+/// Index of `colors` among the numeric capabilities
+const TERMINFO_COLORS: usize = 13;
+
+/// The `colors` capability of a compiled terminfo entry, in either the
+/// legacy 16-bit or the extended 32-bit number format
+fn parse_terminfo_colors(entry: &[u8]) -> Option<u32> {
+    let short = |i: usize| -> Option<usize> {
+        let bytes = entry.get(i * 2..i * 2 + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names_size, bools, numbers) = (short(1)?, short(2)?, short(3)?);
+    if numbers <= TERMINFO_COLORS {
+        return None;
+    }
+
+    // Numbers start on an even offset
+    let mut offset = 12 + names_size + bools;
+    offset += offset % 2;
+    let start = offset + TERMINFO_COLORS * number_size;
+    let bytes = entry.get(start..start + number_size)?;
+
+    let colors = match number_size {
+        2 => i16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+    u32::try_from(colors).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn enabled(choice: ColorChoice, is_terminal: bool, vars: &[(&str, &str)]) -> bool {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        color_enabled(choice, is_terminal, |name| {
+            vars.get(name).map(|value| value.to_string())
+        })
+    }
+
+    fn depth(vars: &[(&str, &str)], terminfo: Option<u32>) -> ColorDepth {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        color_depth(
+            |name| vars.get(name).map(|value| value.to_string()),
+            |_| terminfo,
+        )
+    }
+
+    /// A compiled terminfo entry with `colors` set
+    fn terminfo_entry(magic: u16, colors: i32) -> Vec<u8> {
+        let names = b"test|test terminal\0";
+        let mut entry = Vec::new();
+        for short in [magic, names.len() as u16, 1, 14, 0, 0] {
+            entry.extend(short.to_le_bytes());
+        }
+        entry.extend(names);
+        entry.push(1);
+        if entry.len() % 2 == 1 {
+            entry.push(0);
+        }
+        for i in 0..14 {
+            let value = if i == TERMINFO_COLORS { colors } else { -1 };
+            match magic {
+                0o432 => entry.extend((value as i16).to_le_bytes()),
+                _ => entry.extend(value.to_le_bytes()),
+            }
+        }
+        entry
+    }
+
+    #[test]
+    fn test_color_depth() {
+        use ColorDepth::*;
+
+        assert_eq!(depth(&[("COLORTERM", "truecolor")], Some(8)), TrueColor);
+        assert_eq!(depth(&[("COLORTERM", "24bit")], None), TrueColor);
+        assert_eq!(depth(&[("TERM", "xterm-256color")], Some(256)), Ansi256);
+        assert_eq!(depth(&[("TERM", "xterm-direct")], Some(1 << 24)), TrueColor);
+        assert_eq!(depth(&[("TERM", "linux")], Some(8)), Ansi16);
+        assert_eq!(depth(&[("TERM", "screen-256color")], None), Ansi256);
+        assert_eq!(depth(&[("TERM", "foot-direct")], None), TrueColor);
+        assert_eq!(depth(&[("TERM", "vt100")], None), Ansi16);
+        assert_eq!(depth(&[], None), Ansi16);
+
+        assert_eq!(
+            parse_terminfo_colors(&terminfo_entry(0o432, 256)),
+            Some(256)
+        );
+        assert_eq!(parse_terminfo_colors(&terminfo_entry(0o432, 8)), Some(8));
+        assert_eq!(
+            parse_terminfo_colors(&terminfo_entry(0o1036, 1 << 24)),
+            Some(1 << 24)
+        );
+        assert_eq!(parse_terminfo_colors(&terminfo_entry(0o432, -1)), None);
+        assert_eq!(parse_terminfo_colors(b"not terminfo"), None);
+        assert_eq!(
+            parse_terminfo_colors(&terminfo_entry(0o432, 256)[..20]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_color_enabled() {
+        use ColorChoice::*;
+
+        assert!(enabled(Auto, true, &[("TERM", "xterm-256color")]));
+        assert!(!enabled(Auto, false, &[]));
+        assert!(!enabled(Auto, true, &[("TERM", "dumb")]));
+        assert!(!enabled(Auto, true, &[("NO_COLOR", "1")]));
+        assert!(enabled(Auto, true, &[("NO_COLOR", "")]));
+        assert!(enabled(Auto, false, &[("CLICOLOR_FORCE", "1")]));
+        assert!(!enabled(Auto, false, &[("CLICOLOR_FORCE", "0")]));
+        assert!(!enabled(
+            Auto,
+            false,
+            &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]
+        ));
+
+        assert!(enabled(Always, false, &[("NO_COLOR", "1")]));
+        assert!(!enabled(Never, true, &[("CLICOLOR_FORCE", "1")]));
+    }
+}
@@ -0,0 +1,456 @@
+use crate::layout::text_width;
+use crate::style::Style;
+use crossterm::style::Color;
+
+/// Presets compiled into the binary, selectable with `--preset`.
+const PRESETS: &[(&str, &str)] = &[
+    ("default", include_str!("presets/default.conf")),
+    ("minimal", include_str!("presets/minimal.conf")),
+    (
+        "neofetch-compat",
+        include_str!("presets/neofetch-compat.conf"),
+    ),
+    ("paleofetch", include_str!("presets/paleofetch.conf")),
+    ("pastel", include_str!("presets/pastel.conf")),
+    ("hardware", include_str!("presets/hardware.conf")),
+    ("storage", include_str!("presets/storage.conf")),
+];
+
+/// Keys accepted in `modules`, in the default order
+pub const MODULES: &[&str] = &[
+    "title",
+    "underline",
+    "os",
+    "host",
+    "bios",
+    "board",
+    "chassis",
+    "kernel",
+    "uptime",
+    "packages",
+    "shell",
+    "display",
+    "terminal",
+    "cpu",
+    "cpuusage",
+    "gpu",
+    "memory",
+    "physicalmemory",
+    "swap",
+    "zswap",
+    "disk",
+    "localip",
+    "battery",
+    "locale",
+    "colors",
+];
+
+/// Modules that need a moment to measure and so are only shown on request
+const OPT_IN_MODULES: &[&str] = &["cpuusage"];
+
+/// How usage percentages are shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PercentStyle {
+    Percent,
+    Bar,
+    Both,
+}
+
+/// Where the logo goes relative to the information column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogoPosition {
+    Left,
+    Right,
+    Top,
+    None,
+}
+
+/// How the information column looks: which modules, in what order, and
+/// their colors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub modules: Vec<String>,
+    pub logo_position: LogoPosition,
+    /// Columns between the logo and the information
+    pub logo_padding: usize,
+    pub separator: String,
+    /// Keys are padded to this many columns so values line up; 0 disables it
+    pub key_width: usize,
+    /// Title text, with `{user}` and `{host}` substituted
+    pub title_format: String,
+    pub underline_char: char,
+    /// Key style, over bold in the logo's second (or first) color
+    pub key_color: Option<Style>,
+    pub value_color: Option<Style>,
+    /// Usage below the first threshold is low, at or above the second is high
+    pub percent_thresholds: (u32, u32),
+    /// Styles for low, medium and high usage; `None` leaves percentages plain
+    pub percent_colors: Option<[Style; 3]>,
+    pub percent_style: PercentStyle,
+    /// Number of cells in a bar, borders excluded
+    pub bar_width: usize,
+    /// Characters for the filled and the empty part of a bar
+    pub bar_chars: (char, char),
+    pub bar_border: Option<(char, char)>,
+    /// Style of the empty part; the filled part takes the percentage style
+    pub bar_empty_color: Option<Style>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            modules: MODULES
+                .iter()
+                .filter(|m| !OPT_IN_MODULES.contains(m))
+                .map(|m| m.to_string())
+                .collect(),
+            logo_position: LogoPosition::Left,
+            logo_padding: 2,
+            separator: ": ".to_string(),
+            key_width: 0,
+            title_format: "{user}@{host}".to_string(),
+            underline_char: '-',
+            key_color: None,
+            value_color: None,
+            percent_thresholds: (33, 66),
+            percent_colors: Some([Color::Green.into(), Color::Yellow.into(), Color::Red.into()]),
+            percent_style: PercentStyle::Percent,
+            bar_width: 10,
+            bar_chars: ('■', '□'),
+            bar_border: Some(('[', ']')),
+            bar_empty_color: None,
+        }
+    }
+}
+
+impl Theme {
+    /// A bundled preset by name
+    pub fn preset(name: &str) -> Result<Self, String> {
+        let (_, source) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .ok_or_else(|| format!("Preset '{name}' not found"))?;
+        Self::parse(source).map_err(|e| format!("Preset '{name}': {e}"))
+    }
+
+    /// Names and descriptions (the first comment line) of the bundled presets
+    pub fn list_presets() -> Vec<(&'static str, &'static str)> {
+        PRESETS
+            .iter()
+            .map(|(name, source)| {
+                let description = source
+                    .lines()
+                    .next()
+                    .and_then(|line| line.strip_prefix('#'))
+                    .unwrap_or("")
+                    .trim();
+                (*name, description)
+            })
+            .collect()
+    }
+
+    /// Parse `key = value` lines over the default theme. Values may be
+    /// double-quoted to keep surrounding spaces.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut theme = Theme::default();
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", number + 1))?;
+            let value = unquote(value.trim());
+            theme
+                .set(key.trim(), value)
+                .map_err(|e| format!("line {}: {e}", number + 1))?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Change one setting, as named in preset files
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let optional_style = |value: &str| match value {
+            "none" | "logo" => Ok(None),
+            _ => Style::parse(value).map(Some),
+        };
+
+        match key {
+            "modules" => {
+                let modules: Vec<String> = value
+                    .split(',')
+                    .map(|m| m.trim().to_lowercase())
+                    .filter(|m| !m.is_empty())
+                    .collect();
+                if let Some(unknown) = modules.iter().find(|m| !MODULES.contains(&m.as_str())) {
+                    return Err(format!("unknown module '{unknown}'"));
+                }
+                self.modules = modules;
+            }
+            "logo-position" => {
+                self.logo_position = match value {
+                    "left" => LogoPosition::Left,
+                    "right" => LogoPosition::Right,
+                    "top" => LogoPosition::Top,
+                    "none" => LogoPosition::None,
+                    _ => return Err(format!("invalid logo position '{value}'")),
+                };
+            }
+            "logo-padding" => {
+                self.logo_padding = value
+                    .parse()
+                    .map_err(|_| format!("invalid padding '{value}'"))?;
+            }
+            "separator" => self.separator = value.to_string(),
+            "key-width" => {
+                self.key_width = value
+                    .parse()
+                    .map_err(|_| format!("invalid width '{value}'"))?;
+            }
+            "title-format" => self.title_format = value.to_string(),
+            "underline-char" => {
+                let mut chars = value.chars();
+                self.underline_char = match (chars.next(), chars.next()) {
+                    (Some(c), None) if text_width(&c.to_string()) > 0 => c,
+                    _ => return Err(format!("expected a single character, got '{value}'")),
+                };
+            }
+            "key-color" => self.key_color = optional_style(value)?,
+            "value-color" => self.value_color = optional_style(value)?,
+            "percent-thresholds" => {
+                let (low, high) = value
+                    .split_once(',')
+                    .and_then(|(low, high)| {
+                        Some((low.trim().parse().ok()?, high.trim().parse().ok()?))
+                    })
+                    .filter(|(low, high)| low <= high)
+                    .ok_or(format!("invalid thresholds '{value}'"))?;
+                self.percent_thresholds = (low, high);
+            }
+            "percent-colors" => {
+                if value == "none" {
+                    self.percent_colors = None;
+                } else {
+                    let colors = value
+                        .split(',')
+                        .map(|c| Style::parse(c.trim()))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let colors: [Style; 3] = colors
+                        .try_into()
+                        .map_err(|_| "expected three colors".to_string())?;
+                    self.percent_colors = Some(colors);
+                }
+            }
+            "percent-style" => {
+                self.percent_style = match value {
+                    "percent" => PercentStyle::Percent,
+                    "bar" => PercentStyle::Bar,
+                    "both" => PercentStyle::Both,
+                    _ => return Err(format!("invalid percent style '{value}'")),
+                };
+            }
+            "bar-width" => {
+                self.bar_width = value
+                    .parse()
+                    .ok()
+                    .filter(|width| (1..=100).contains(width))
+                    .ok_or(format!("invalid bar width '{value}'"))?;
+            }
+            "bar-chars" => self.bar_chars = char_pair(value)?,
+            "bar-border" => {
+                self.bar_border = match value {
+                    "none" => None,
+                    _ => Some(char_pair(value)?),
+                };
+            }
+            "bar-empty-color" => self.bar_empty_color = optional_style(value)?,
+            _ => return Err(format!("unknown setting '{key}'")),
+        }
+
+        Ok(())
+    }
+
+    /// The title split into literal text and the substituted user and host
+    pub fn title_parts(&self, user: &str, host: &str) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = self.title_format.as_str();
+
+        while let Some(c) = rest.chars().next() {
+            let placeholder = [("{user}", user), ("{host}", host)]
+                .into_iter()
+                .find(|(name, _)| rest.starts_with(name));
+            match placeholder {
+                Some((name, value)) => {
+                    if !literal.is_empty() {
+                        parts.push(std::mem::take(&mut literal));
+                    }
+                    parts.push(value.to_string());
+                    rest = &rest[name.len()..];
+                }
+                None => {
+                    literal.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(literal);
+        }
+
+        parts
+    }
+
+    /// Underline covering `text`, measured in terminal columns
+    pub fn underline(&self, text: &str) -> String {
+        let char_width = text_width(&self.underline_char.to_string()).max(1);
+        self.underline_char
+            .to_string()
+            .repeat(text_width(text).div_ceil(char_width))
+    }
+
+    /// `key` padded with spaces to the key width
+    pub fn pad_key(&self, key: &str) -> String {
+        crate::layout::padding(text_width(key), self.key_width)
+    }
+
+    /// Filled and empty cells of the bar for a percentage
+    pub fn bar_cells(&self, percent: u32) -> (usize, usize) {
+        let filled = (percent.min(100) as usize * self.bar_width + 50) / 100;
+        (filled, self.bar_width - filled)
+    }
+
+    pub fn shows(&self, module: &str) -> bool {
+        self.modules.iter().any(|m| m == module)
+    }
+
+    /// Style for a usage percentage, if percentages are colored
+    pub fn percent_color(&self, percent: u32) -> Option<Style> {
+        let [low, medium, high] = self.percent_colors?;
+        let (low_max, high_min) = self.percent_thresholds;
+        Some(if percent < low_max {
+            low
+        } else if percent < high_min {
+            medium
+        } else {
+            high
+        })
+    }
+}
+
+fn char_pair(value: &str) -> Result<(char, char), String> {
+    let chars: Vec<char> = value.chars().collect();
+    match chars[..] {
+        [first, second] => Ok((first, second)),
+        _ => Err(format!("expected two characters, got '{value}'")),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_presets_parse() {
+        for (name, _) in Theme::list_presets() {
+            assert!(Theme::preset(name).is_ok(), "preset {name} is invalid");
+        }
+        assert_eq!(Theme::preset("default").unwrap(), Theme::default());
+        assert!(Theme::preset("nope").is_err());
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let theme = Theme::parse(
+            "# comment\nmodules = title, os, memory\nseparator = \" -> \"\nkey-color = #ff8800\npercent-thresholds = 50, 80\npercent-colors = none\n",
+        )
+        .unwrap();
+        assert_eq!(theme.modules, vec!["title", "os", "memory"]);
+        assert_eq!(theme.separator, " -> ");
+        assert_eq!(
+            theme.key_color,
+            Some(Style::from(Color::Rgb {
+                r: 255,
+                g: 136,
+                b: 0
+            }))
+        );
+        assert_eq!(theme.percent_thresholds, (50, 80));
+        assert_eq!(theme.percent_color(90), None);
+
+        assert!(Theme::parse("modules = os, nonsense").is_err());
+        assert!(Theme::parse("key-color = not-a-color").is_err());
+        assert!(Theme::parse("percent-colors = red, green").is_err());
+        assert!(Theme::parse("percent-thresholds = 80, 50").is_err());
+        assert!(Theme::parse("bogus = 1").is_err());
+    }
+
+    #[test]
+    fn test_title_and_key_width() {
+        let mut theme = Theme::default();
+        assert_eq!(theme.title_parts("bob", "box"), vec!["bob", "@", "box"]);
+        assert_eq!(theme.underline("ユーザー@box"), "-".repeat(12));
+        assert_eq!(theme.pad_key("OS"), "");
+
+        theme.set("title-format", "[{host}] {user} {x}").unwrap();
+        theme.set("underline-char", "═").unwrap();
+        theme.set("key-width", "6").unwrap();
+        assert_eq!(
+            theme.title_parts("bob", "box"),
+            vec!["[", "box", "] ", "bob", " {x}"]
+        );
+        assert_eq!(theme.underline("bob"), "═══");
+        assert_eq!(theme.pad_key("CPU"), "   ");
+        assert_eq!(theme.pad_key("名前"), "  ");
+        assert_eq!(theme.pad_key("Physical Memory"), "");
+
+        assert!(theme.set("underline-char", "ab").is_err());
+        assert!(theme.set("underline-char", "").is_err());
+        assert!(theme.set("key-width", "-1").is_err());
+    }
+
+    #[test]
+    fn test_bar_settings() {
+        let mut theme = Theme::default();
+        assert!(!theme.shows("cpuusage"));
+        assert_eq!(theme.bar_cells(0), (0, 10));
+        assert_eq!(theme.bar_cells(25), (3, 7));
+        assert_eq!(theme.bar_cells(100), (10, 0));
+        assert_eq!(theme.bar_cells(250), (10, 0));
+
+        theme.set("percent-style", "both").unwrap();
+        theme.set("bar-width", "4").unwrap();
+        theme.set("bar-chars", "#-").unwrap();
+        theme.set("bar-border", "none").unwrap();
+        assert_eq!(theme.percent_style, PercentStyle::Both);
+        assert_eq!(theme.bar_cells(50), (2, 2));
+        assert_eq!(theme.bar_chars, ('#', '-'));
+        assert_eq!(theme.bar_border, None);
+
+        assert!(theme.set("percent-style", "pie").is_err());
+        assert!(theme.set("logo-position", "bottom").is_err());
+        assert!(theme.set("logo-padding", "wide").is_err());
+        assert!(theme.set("bar-width", "0").is_err());
+        assert!(theme.set("bar-chars", "#").is_err());
+        assert!(theme.set("bar-border", "<|>").is_err());
+    }
+
+    #[test]
+    fn test_percent_color() {
+        let theme = Theme::default();
+        assert_eq!(theme.percent_color(32), Some(Color::Green.into()));
+        assert_eq!(theme.percent_color(33), Some(Color::Yellow.into()));
+        assert_eq!(theme.percent_color(66), Some(Color::Red.into()));
+    }
+}
@@ -0,0 +1,416 @@
+use crate::colors::{LogoEntry, ansi_to_crossterm_color};
+use crossterm::style::Color;
+
+/// Header of the generated logo table
+const HEADER: &str = "\
+# Logo colors, generated from upstream fastfetch's src/logo/builtin.c with
+#   fastfetch-rs --import-logos builtin.c > src/logo/logos.conf
+# One section per logo: its name, then other names that select it, its
+# colors ($1, $2, ...) and the key and title colors when they differ.
+";
+
+/// Convert upstream fastfetch's `builtin.c` logo definitions into the
+/// logo table format. A name already taken by an earlier logo is dropped,
+/// as upstream's lookup would never reach it.
+pub fn convert(source: &str) -> Result<String, String> {
+    let source = strip_comments(source);
+    let mut taken: Vec<String> = Vec::new();
+    let mut output = HEADER.to_string();
+    let mut count = 0;
+
+    for definition in source.split(".names").skip(1) {
+        let names = braced(definition)
+            .map(string_literals)
+            .ok_or("`.names` without a `{...}` list")?;
+        let mut names = names
+            .iter()
+            .map(|name| name.to_lowercase())
+            .filter(|name| !name.contains([',', '[', ']']) && !name.trim().is_empty())
+            .filter(|name| !taken.contains(name))
+            .collect::<Vec<_>>();
+        names.dedup();
+        let Some(name) = names.first().cloned() else {
+            continue;
+        };
+        taken.extend(names.iter().cloned());
+
+        let file = setting(definition, ".lines")
+            .and_then(|lines| lines.strip_prefix("FASTFETCH_DATATEXT_LOGO_"))
+            .map(|file| file.to_lowercase())
+            .filter(|file| *file != name);
+
+        let colors = match setting(definition, ".colors").and_then(braced) {
+            Some(list) => split_items(list)
+                .map(|item| parse_color(item).ok_or(format!("{name}: unknown color {item}")))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        let optional_color = |key: &str| match setting(definition, key) {
+            Some(item) => parse_color(item)
+                .map(Some)
+                .ok_or(format!("{name}: unknown color {item}")),
+            None => Ok(None),
+        };
+
+        let entry = LogoEntry {
+            aliases: names[1..].to_vec(),
+            file,
+            colors,
+            key_color: optional_color(".colorKeys")?,
+            title_color: optional_color(".colorTitle")?,
+            name,
+            ..LogoEntry::default()
+        };
+        output.push_str(&write_entry(&entry));
+        count += 1;
+    }
+
+    if count == 0 {
+        return Err("no logo definitions found".to_string());
+    }
+    Ok(output)
+}
+
+/// One `[name]` section of the logo table
+fn write_entry(entry: &LogoEntry) -> String {
+    let mut section = format!("\n[{}]\n", entry.name);
+    if !entry.aliases.is_empty() {
+        section.push_str(&format!("aliases = {}\n", entry.aliases.join(", ")));
+    }
+    if let Some(file) = &entry.file {
+        section.push_str(&format!("file = {file}\n"));
+    }
+    if !entry.colors.is_empty() {
+        let colors: Vec<String> = entry.colors.iter().map(|&c| color_name(c)).collect();
+        section.push_str(&format!("colors = {}\n", colors.join(", ")));
+    }
+    if let Some(color) = entry.key_color {
+        section.push_str(&format!("key-color = {}\n", color_name(color)));
+    }
+    if let Some(color) = entry.title_color {
+        section.push_str(&format!("title-color = {}\n", color_name(color)));
+    }
+    if let Some(small) = &entry.small {
+        section.push_str(&format!("small = {small}\n"));
+    }
+    section
+}
+
+/// `source` without `//` and `/* */` comments, leaving string literals alone
+fn strip_comments(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(_)) if in_string => {
+                result.push(ch);
+                result.extend(chars.next());
+            }
+            ('"', _) => {
+                in_string = !in_string;
+                result.push(ch);
+            }
+            ('/', Some('/')) if !in_string => {
+                if chars.any(|c| c == '\n') {
+                    result.push('\n');
+                }
+            }
+            ('/', Some('*')) if !in_string => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// The value of `.key = value,` within one definition
+fn setting<'a>(definition: &'a str, key: &str) -> Option<&'a str> {
+    let start = definition.find(&format!("{key} "))?;
+    let value = definition[start + key.len()..].trim_start();
+    let value = value.strip_prefix('=')?.trim_start();
+
+    if value.starts_with('{') {
+        return Some(value);
+    }
+    let end = value.find([',', '\n', '}']).unwrap_or(value.len());
+    Some(value[..end].trim())
+}
+
+/// The contents of the `{...}` list at the start of `text`
+fn braced(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let end = start + text[start..].find('}')?;
+    Some(&text[start + 1..end])
+}
+
+/// The comma-separated, non-empty items of a list
+fn split_items(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// Every string literal in `text`, with escapes resolved
+fn string_literals(text: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut chars = text.chars();
+
+    while chars.any(|c| c == '"') {
+        let mut literal = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => literal.extend(chars.next()),
+                _ => literal.push(c),
+            }
+        }
+        literals.push(literal);
+    }
+    literals
+}
+
+/// An upstream color: an `FF_COLOR_FG_*` name, or SGR parameters such as
+/// `"36"`, `FF_COLOR_FG_256 "208"`, `"38;5;208"` or `"38;2;255;136;0"`.
+/// Like the names, `"36"` is the bright color logos show in; only a code
+/// that turns bold off, as in `"22;36"`, gives the dark one.
+fn parse_color(item: &str) -> Option<Color> {
+    if let Some(color) = ansi_to_crossterm_color(item) {
+        return Some(color);
+    }
+
+    let sgr = match item.strip_prefix("FF_COLOR_FG_256") {
+        Some(index) => format!("38;5;{}", string_literals(index).concat()),
+        None => string_literals(item).concat(),
+    };
+    let params: Vec<u8> = sgr
+        .split(';')
+        .map(|param| param.parse().ok())
+        .collect::<Option<_>>()?;
+
+    // A leading bold is how upstream brightens some colors; logos are bold anyway
+    let params = params.strip_prefix(&[1]).unwrap_or(&params);
+    let names = [
+        "BLACK", "RED", "GREEN", "YELLOW", "BLUE", "MAGENTA", "CYAN", "WHITE",
+    ];
+    let dark = [
+        Color::Black,
+        Color::DarkRed,
+        Color::DarkGreen,
+        Color::DarkYellow,
+        Color::DarkBlue,
+        Color::DarkMagenta,
+        Color::DarkCyan,
+        Color::Grey,
+    ];
+    match params {
+        [code @ 30..=37] => {
+            ansi_to_crossterm_color(&format!("FF_COLOR_FG_{}", names[(code - 30) as usize]))
+        }
+        [code @ 90..=97] => ansi_to_crossterm_color(&format!(
+            "FF_COLOR_FG_LIGHT_{}",
+            names[(code - 90) as usize]
+        )),
+        [22, code @ 30..=37] => Some(dark[(code - 30) as usize]),
+        [38, 5, index] => Some(Color::AnsiValue(*index)),
+        [38, 2, r, g, b] => Some(Color::Rgb {
+            r: *r,
+            g: *g,
+            b: *b,
+        }),
+        _ => None,
+    }
+}
+
+/// The name of `color` in the vocabulary `parse_color` reads
+fn color_name(color: Color) -> String {
+    let name = match color {
+        Color::Black => "black",
+        Color::DarkRed => "darkred",
+        Color::DarkGreen => "darkgreen",
+        Color::DarkYellow => "darkyellow",
+        Color::DarkBlue => "darkblue",
+        Color::DarkMagenta => "darkmagenta",
+        Color::DarkCyan => "darkcyan",
+        Color::Grey => "grey",
+        Color::DarkGrey => "darkgrey",
+        Color::Red => "red",
+        Color::Green => "green",
+        Color::Yellow => "yellow",
+        Color::Blue => "blue",
+        Color::Magenta => "magenta",
+        Color::Cyan => "cyan",
+        Color::White => "white",
+        Color::AnsiValue(index) => return index.to_string(),
+        Color::Rgb { r, g, b } => return format!("#{r:02x}{g:02x}{b:02x}"),
+        _ => "default",
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::parse_logo_table;
+
+    const BUILTIN: &str = r#"
+static const FFlogo A[] = {
+    // Arch
+    {
+        .names = {"Arch", "archlinux", "arch-linux"},
+        .lines = FASTFETCH_DATATEXT_LOGO_ARCH,
+        .colors = {
+            FF_COLOR_FG_CYAN,
+            FF_COLOR_FG_CYAN,
+        },
+        .colorKeys = FF_COLOR_FG_CYAN,
+        .colorTitle = FF_COLOR_FG_CYAN,
+    },
+    // ArchSmall
+    {
+        .names = {"arch_small", "archlinux_small", "arch-linux-small"},
+        .type = FF_LOGO_LINE_TYPE_SMALL_BIT,
+        .lines = FASTFETCH_DATATEXT_LOGO_ARCH_SMALL,
+        .colors = {
+            "36",
+        },
+    },
+    /* Pop!_OS */
+    {
+        .names = {"Pop!_OS", "popos", "pop_os", "arch"},
+        .lines = FASTFETCH_DATATEXT_LOGO_POP,
+        .colors = {
+            FF_COLOR_FG_256 "208",
+            "38;2;255;136;0",
+            FF_COLOR_FG_DEFAULT,
+        },
+        .colorTitle = FF_COLOR_FG_LIGHT_WHITE,
+    },
+    // LAST
+    {},
+};
+"#;
+
+    #[test]
+    fn test_convert_builtin() {
+        let table = convert(BUILTIN).unwrap();
+        assert!(table.starts_with("# Logo colors"));
+        assert!(table.contains(
+            "\n[pop!_os]\naliases = popos, pop_os\nfile = pop\ncolors = 208, #ff8800, default\ntitle-color = white\n"
+        ));
+
+        let entries = parse_logo_table(&table).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "arch");
+        assert_eq!(entries[0].aliases, ["archlinux", "arch-linux"]);
+        assert_eq!(entries[0].file, None);
+        assert_eq!(entries[0].colors, [Color::Cyan, Color::Cyan]);
+        assert_eq!(entries[0].key_color, Some(Color::Cyan));
+        assert_eq!(entries[1].name, "arch_small");
+        assert_eq!(entries[1].colors, [Color::Cyan]);
+        assert_eq!(
+            entries[2].colors,
+            [
+                Color::AnsiValue(208),
+                Color::Rgb {
+                    r: 255,
+                    g: 136,
+                    b: 0
+                },
+                Color::Reset
+            ]
+        );
+
+        // Both spellings of an upstream color give the same color
+        assert_eq!(parse_color("\"36\""), parse_color("FF_COLOR_FG_CYAN"));
+        assert_eq!(parse_color("\"1;36\""), Some(Color::Cyan));
+        assert_eq!(
+            parse_color("\"97\""),
+            parse_color("FF_COLOR_FG_LIGHT_WHITE")
+        );
+        assert_eq!(parse_color("\"30\""), Some(Color::Black));
+        assert_eq!(parse_color("\"22;36\""), Some(Color::DarkCyan));
+
+        assert!(convert("int main() {}").is_err());
+        assert!(convert(r#".names = {"x"}, .colors = {FF_COLOR_FG_SPARKLY},"#).is_err());
+    }
+
+    /// `entry` defined the way upstream's builtin.c defines a logo
+    fn builtin_definition(entry: &LogoEntry) -> String {
+        let quoted: Vec<String> = [&entry.name]
+            .into_iter()
+            .chain(&entry.aliases)
+            .map(|name| format!("\"{name}\""))
+            .collect();
+        let file = entry.file.as_ref().unwrap_or(&entry.name);
+        let colors: Vec<String> = entry.colors.iter().map(|&c| upstream_color(c)).collect();
+
+        let mut definition = format!(
+            "    {{\n        .names = {{{}}},\n        .lines = FASTFETCH_DATATEXT_LOGO_{},\n        .colors = {{\n            {},\n        }},\n",
+            quoted.join(", "),
+            file.to_uppercase(),
+            colors.join(",\n            ")
+        );
+        if let Some(color) = entry.key_color {
+            definition.push_str(&format!(
+                "        .colorKeys = {},\n",
+                upstream_color(color)
+            ));
+        }
+        if let Some(color) = entry.title_color {
+            definition.push_str(&format!(
+                "        .colorTitle = {},\n",
+                upstream_color(color)
+            ));
+        }
+        definition + "    },\n"
+    }
+
+    /// How upstream spells `color`
+    fn upstream_color(color: Color) -> String {
+        let name = match color {
+            Color::Black => "BLACK",
+            Color::DarkGrey => "LIGHT_BLACK",
+            Color::Red => "RED",
+            Color::Green => "GREEN",
+            Color::Yellow => "YELLOW",
+            Color::Blue => "BLUE",
+            Color::Magenta => "MAGENTA",
+            Color::Cyan => "CYAN",
+            Color::White => "WHITE",
+            Color::Reset => "DEFAULT",
+            Color::DarkRed => return "\"22;31\"".to_string(),
+            Color::DarkGreen => return "\"22;32\"".to_string(),
+            Color::DarkYellow => return "\"22;33\"".to_string(),
+            Color::DarkBlue => return "\"22;34\"".to_string(),
+            Color::DarkMagenta => return "\"22;35\"".to_string(),
+            Color::DarkCyan => return "\"22;36\"".to_string(),
+            Color::Grey => return "\"22;37\"".to_string(),
+            Color::AnsiValue(index) => return format!("FF_COLOR_FG_256 \"{index}\""),
+            Color::Rgb { r, g, b } => return format!("\"38;2;{r};{g};{b}\""),
+        };
+        format!("FF_COLOR_FG_{name}")
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        // Converting the table's logos back from upstream's format gives
+        // the table again, so regenerating it changes no colors
+        let table = include_str!("logo/logos.conf");
+        let entries = parse_logo_table(table).unwrap();
+        let builtin: String = entries.iter().map(builtin_definition).collect();
+
+        let converted = convert(&format!("static const FFlogo L[] = {{\n{builtin}}};\n")).unwrap();
+        assert_eq!(parse_logo_table(&converted).unwrap(), entries);
+        assert_eq!(converted, table);
+    }
+}